@@ -0,0 +1,62 @@
+// Rusted Quotes embed loader.
+//
+//   <div data-rusted-quote="daily" data-theme="light"></div>
+//   <script async src="https://quotes.example.com/embed.js"></script>
+//
+// Every element with `data-rusted-quote` ("random", "daily" or a quote id)
+// is replaced by an iframe of the server's /embed page. `data-theme`,
+// `data-tag`, `data-author`, `data-lang` and `data-refresh` are passed
+// through. Frames report their content height so they never scroll.
+(function () {
+  var origin = __ORIGIN__;
+  var options = ['theme', 'tag', 'author', 'lang', 'refresh'];
+
+  function mount(element) {
+    if (element.getAttribute('data-rusted-mounted')) {
+      return;
+    }
+    element.setAttribute('data-rusted-mounted', 'true');
+
+    var params = new URLSearchParams();
+    params.set('quote', element.getAttribute('data-rusted-quote') || 'random');
+    options.forEach(function (name) {
+      var value = element.getAttribute('data-' + name);
+      if (value) {
+        params.set(name, value);
+      }
+    });
+
+    var frame = document.createElement('iframe');
+    frame.src = origin + '/embed?' + params.toString();
+    frame.title = 'Quote from Rusted Quotes';
+    frame.loading = 'lazy';
+    frame.style.cssText = 'display:block;border:0;width:100%;max-width:640px;height:180px;';
+    element.appendChild(frame);
+  }
+
+  window.addEventListener('message', function (event) {
+    var data = event.data;
+    if (event.origin !== origin || !data || data.type !== 'rusted-quotes:resize') {
+      return;
+    }
+    var frames = document.querySelectorAll('[data-rusted-quote] > iframe');
+    for (var i = 0; i < frames.length; i++) {
+      if (frames[i].contentWindow === event.source) {
+        frames[i].style.height = Math.ceil(data.height) + 'px';
+      }
+    }
+  });
+
+  function mountAll() {
+    var elements = document.querySelectorAll('[data-rusted-quote]');
+    for (var i = 0; i < elements.length; i++) {
+      mount(elements[i]);
+    }
+  }
+
+  if (document.readyState === 'loading') {
+    document.addEventListener('DOMContentLoaded', mountAll);
+  } else {
+    mountAll();
+  }
+})();
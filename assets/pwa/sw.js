@@ -0,0 +1,67 @@
+// Rusted Quotes service worker.
+//
+// Keeps the app shell (the page, the wasm bundle, Tailwind and the fonts)
+// available offline. Requests go to the network first and fall back to the
+// cache, so a new deploy shows up on the next load. API responses are left
+// alone: the app keeps its own copy of quotes in IndexedDB.
+var CACHE = 'rusted-quotes-shell-v1';
+var SHELL = ['./', './index.html', './manifest.webmanifest', './icon-192.png', './icon-512.png'];
+var CDN_HOSTS = ['cdn.tailwindcss.com', 'fonts.googleapis.com', 'fonts.gstatic.com'];
+
+self.addEventListener('install', function (event) {
+  event.waitUntil(
+    caches.open(CACHE)
+      .then(function (cache) { return cache.addAll(SHELL); })
+      .then(function () { return self.skipWaiting(); })
+  );
+});
+
+self.addEventListener('activate', function (event) {
+  event.waitUntil(
+    caches.keys()
+      .then(function (keys) {
+        return Promise.all(keys.filter(function (key) { return key !== CACHE; }).map(function (key) {
+          return caches.delete(key);
+        }));
+      })
+      .then(function () { return self.clients.claim(); })
+  );
+});
+
+function handles(request) {
+  if (request.method !== 'GET') {
+    return false;
+  }
+  var url = new URL(request.url);
+  if (url.origin === self.location.origin) {
+    return url.pathname.indexOf('/api/') !== 0;
+  }
+  return CDN_HOSTS.indexOf(url.hostname) !== -1;
+}
+
+function networkFirst(request) {
+  return fetch(request)
+    .then(function (response) {
+      // Cross-origin scripts and stylesheets come back opaque.
+      if (response.ok || response.type === 'opaque') {
+        var copy = response.clone();
+        caches.open(CACHE).then(function (cache) { cache.put(request, copy); });
+      }
+      return response;
+    })
+    .catch(function () {
+      return caches.match(request).then(function (cached) {
+        if (cached) {
+          return cached;
+        }
+        // Deep links (#/quotes/…) all load the same page.
+        return request.mode === 'navigate' ? caches.match('./index.html') : Response.error();
+      });
+    });
+}
+
+self.addEventListener('fetch', function (event) {
+  if (handles(event.request)) {
+    event.respondWith(networkFirst(event.request));
+  }
+});
@@ -10,7 +10,14 @@ module.exports = {
       env: {
         NODE_ENV: '',
         DATABASE_URL: '',
-        INAPPROPRIATE_WORDS: ''
+        INAPPROPRIATE_WORDS: '',
+        ADMIN_TOKEN: '',
+        DAILY_TIMEZONE: 'UTC',
+        DAILY_NO_REPEAT_DAYS: '30',
+        TRASH_RETENTION_DAYS: '30',
+        TRUSTED_PROXIES: '',
+        PUBLIC_URL: 'http://localhost:3000',
+        APP_URL: 'http://localhost:8080'
       },
       env_production: {
         NODE_ENV: 'production'
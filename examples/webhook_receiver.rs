@@ -0,0 +1,69 @@
+//! A local endpoint for trying out webhooks.
+//!
+//! ```text
+//! WEBHOOK_SECRET=<secret> cargo run --example webhook_receiver --features server
+//! ```
+//!
+//! Register `http://localhost:4000/webhook` through `POST /api/admin/webhooks`
+//! with the same secret, then call `POST /api/admin/webhooks/:id/test` or add a
+//! quote. Every request is printed along with whether its signature checks out;
+//! set `WEBHOOK_FAIL=1` to answer 500 and watch the server retry.
+
+use axum::{ body::Bytes, http::{ HeaderMap, StatusCode }, routing::post, Router };
+use hmac::{ Hmac, Mac };
+use sha2::Sha256;
+use std::env;
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default()
+}
+
+fn signature_matches(secret: &str, timestamp: &str, body: &[u8], signature: &str) -> bool {
+    let Some(signature) = signature.strip_prefix("sha256=").and_then(|hex| hex::decode(hex).ok()) else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+async fn receive(headers: HeaderMap, body: Bytes) -> StatusCode {
+    let secret = env::var("WEBHOOK_SECRET").unwrap_or_default();
+    let verified = signature_matches(
+        &secret,
+        header(&headers, "x-webhook-timestamp"),
+        &body,
+        header(&headers, "x-webhook-signature")
+    );
+
+    println!(
+        "{} delivery {} ({}): {}",
+        header(&headers, "x-webhook-event"),
+        header(&headers, "x-webhook-delivery"),
+        if verified { "signature ok" } else { "BAD SIGNATURE" },
+        String::from_utf8_lossy(&body)
+    );
+
+    if env::var("WEBHOOK_FAIL").is_ok_and(|fail| fail == "1") {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else if verified {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::UNAUTHORIZED
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port = env::var("WEBHOOK_PORT").unwrap_or_else(|_| "4000".to_string());
+    let app = Router::new().route("/webhook", post(receive));
+
+    let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
+    println!("Listening for webhooks on http://127.0.0.1:{}/webhook", port);
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
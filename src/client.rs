@@ -0,0 +1,402 @@
+//! Typed access to the REST API.
+//!
+//! Every call checks the response status and decodes the body into the
+//! matching type, so callers get a `ClientError` instead of a panic when the
+//! server is down, answers with an error, or sends something unexpected.
+//! `QuoteManager` wraps these calls for JavaScript.
+//!
+//! Quote reads are saved to IndexedDB (see [`crate::offline`]) and answered
+//! from there when the API cannot be reached.
+
+use serde::{ de::DeserializeOwned, Serialize };
+use std::fmt;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ window, Headers, Request, RequestInit, RequestMode, Response };
+
+use crate::{ client_id, encode_component, offline, set_reachable, Citation, Collection, CollectionDetail, LikeStatus, Quote };
+
+/// Sent by `GET /quotes` when there is another page.
+const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request never got an answer: offline, CORS, or a bad URL.
+    Network(String),
+    /// The server answered with a non-2xx status.
+    Http {
+        status: u16,
+        method: String,
+        path: String,
+    },
+    /// The body was not the JSON we expected.
+    Decode(String),
+}
+
+impl ClientError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ClientError::Http { status: 404, .. })
+    }
+
+    /// The server could not be reached, directly or through its proxy, so
+    /// the request is worth retrying later.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, ClientError::Network(_) | ClientError::Http { status: 502..=504, .. })
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Network(message) => write!(f, "Network error: {}", message),
+            ClientError::Http { status, method, path } => write!(f, "HTTP {}: {} {} failed", status, method, path),
+            ClientError::Decode(message) => write!(f, "Unexpected response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<JsValue> for ClientError {
+    fn from(value: JsValue) -> Self {
+        ClientError::Network(value.as_string().unwrap_or_else(|| format!("{:?}", value)))
+    }
+}
+
+impl From<ClientError> for JsValue {
+    fn from(error: ClientError) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
+
+/// The orders `GET /quotes` can sort by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteSort {
+    #[default]
+    Newest,
+    Oldest,
+    /// Most liked first.
+    Popular,
+    Random,
+}
+
+impl QuoteSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuoteSort::Newest => "newest",
+            QuoteSort::Oldest => "oldest",
+            QuoteSort::Popular => "popular",
+            QuoteSort::Random => "random",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<QuoteSort> {
+        match value {
+            "newest" => Some(QuoteSort::Newest),
+            "oldest" => Some(QuoteSort::Oldest),
+            "popular" => Some(QuoteSort::Popular),
+            "random" => Some(QuoteSort::Random),
+            _ => None,
+        }
+    }
+}
+
+/// Filters, order and page size for `GET /quotes`; unset fields and the
+/// default order are left out of the request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuoteListQuery {
+    pub search: Option<String>,
+    pub tag: Option<String>,
+    /// Exact author name, case-insensitive.
+    pub author: Option<String>,
+    pub sort: QuoteSort,
+    /// Quotes per page; the server's default when unset.
+    pub limit: Option<u32>,
+}
+
+impl QuoteListQuery {
+    pub fn is_filtered(&self) -> bool {
+        self.search.is_some() || self.tag.is_some() || self.author.is_some()
+    }
+
+    /// Quotes by `name`, keeping this list's order and page size.
+    pub fn for_author(&self, name: &str) -> QuoteListQuery {
+        QuoteListQuery { author: Some(name.to_string()), sort: self.sort, limit: self.limit, ..Default::default() }
+    }
+
+    /// Quotes tagged `tag`, keeping this list's order and page size.
+    pub fn for_tag(&self, tag: &str) -> QuoteListQuery {
+        QuoteListQuery { tag: Some(tag.to_string()), sort: self.sort, limit: self.limit, ..Default::default() }
+    }
+
+    /// `?search=…&sort=…`, or an empty string when nothing is set.
+    pub fn query_string(&self) -> String {
+        let sort = (self.sort != QuoteSort::default()).then(|| self.sort.as_str().to_string());
+        let limit = self.limit.map(|limit| limit.to_string());
+        let params: Vec<String> = [
+            ("search", &self.search),
+            ("tag", &self.tag),
+            ("author", &self.author),
+            ("sort", &sort),
+            ("limit", &limit),
+        ]
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value.as_ref().map(|value| format!("{}={}", name, encode_component(value)))
+            })
+            .collect();
+        if params.is_empty() { String::new() } else { format!("?{}", params.join("&")) }
+    }
+}
+
+/// One page of `GET /quotes`.
+#[derive(Debug, Clone)]
+pub struct QuotePage {
+    pub quotes: Vec<Quote>,
+    /// Fetches the page after this one; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CreateQuote<'a> {
+    quote: &'a str,
+    author: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submission_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct CreateCollection<'a> {
+    name: &'a str,
+    is_public: bool,
+}
+
+#[derive(Serialize)]
+struct AddToCollection<'a> {
+    quote_id: &'a str,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    base: String,
+}
+
+impl ApiClient {
+    /// A client for the API at `base`, e.g. `https://quotes.example.com/api`.
+    pub fn new(base: &str) -> Self {
+        ApiClient {
+            base: base.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    pub async fn quotes(&self, query: &QuoteListQuery) -> Result<Vec<Quote>, ClientError> {
+        self.quotes_page(query, None).await.map(|page| page.quotes)
+    }
+
+    /// The first page of `query`, or the one at `cursor` from a previous
+    /// page's `next_cursor`.
+    pub async fn quotes_page(&self, query: &QuoteListQuery, cursor: Option<&str>) -> Result<QuotePage, ClientError> {
+        let path = format!("/quotes{}", query.query_string());
+        let (quotes, next_cursor): (Vec<Quote>, _) = match cursor {
+            // Only first pages are kept for offline use; a replayed page has
+            // no cursor, so an offline list ends with it.
+            None => self.get_saved_page(&path).await?,
+            Some(cursor) => {
+                let separator = if path.contains('?') { '&' } else { '?' };
+                let path = format!("{}{}cursor={}", path, separator, encode_component(cursor));
+                let resp = self.fetch("GET", &path, None).await?;
+                let next_cursor = next_cursor(&resp);
+                (decode("GET", &path, &response_text(resp).await?)?, next_cursor)
+            }
+        };
+
+        // Lets a quote seen in a list be opened offline too.
+        for quote in &quotes {
+            if let Ok(json) = serde_json::to_string(quote) {
+                save_response(format!("/quotes/{}", encode_component(&quote.id)), json);
+            }
+        }
+        Ok(QuotePage { quotes, next_cursor })
+    }
+
+    pub async fn quote(&self, id: &str) -> Result<Quote, ClientError> {
+        self.get_saved(&format!("/quotes/{}", encode_component(id))).await
+    }
+
+    /// Sending the same `submission_id` again returns the quote created the
+    /// first time, so queued submissions can be retried safely.
+    pub async fn create_quote(
+        &self,
+        quote: &str,
+        author: Option<&str>,
+        submission_id: Option<&str>
+    ) -> Result<Quote, ClientError> {
+        self.send("POST", "/quotes", Some(&CreateQuote { quote, author, submission_id })).await
+    }
+
+    /// `session` keeps repeated calls walking one shuffled deck; see
+    /// `RANDOM_SESSION`.
+    pub async fn random_quote(&self, session: Option<&str>) -> Result<Quote, ClientError> {
+        match session {
+            Some(session) => self.get(&format!("/quotes/random?session={}", encode_component(session))).await,
+            None => self.get("/quotes/random").await,
+        }
+    }
+
+    pub async fn daily_quote(&self) -> Result<Quote, ClientError> {
+        self.get_saved("/quotes/daily").await
+    }
+
+    pub async fn like(&self, id: &str) -> Result<LikeStatus, ClientError> {
+        self.send("POST", &format!("/quotes/{}/like", encode_component(id)), None::<&()>).await
+    }
+
+    pub async fn unlike(&self, id: &str) -> Result<LikeStatus, ClientError> {
+        self.send("DELETE", &format!("/quotes/{}/like", encode_component(id)), None::<&()>).await
+    }
+
+    /// The other language versions of a quote, original first.
+    pub async fn translations(&self, id: &str) -> Result<Vec<Quote>, ClientError> {
+        self.get(&format!("/quotes/{}/translations", encode_component(id))).await
+    }
+
+    /// `style` is one of `apa`, `mla` or `chicago`.
+    pub async fn citation(&self, id: &str, style: &str) -> Result<Citation, ClientError> {
+        self.get(&format!("/quotes/{}/cite?style={}", encode_component(id), encode_component(style))).await
+    }
+
+    pub async fn collections(&self, mine: bool) -> Result<Vec<Collection>, ClientError> {
+        self.get(if mine { "/collections?mine=true" } else { "/collections" }).await
+    }
+
+    pub async fn collection(&self, slug: &str) -> Result<CollectionDetail, ClientError> {
+        self.get(&format!("/collections/{}", encode_component(slug))).await
+    }
+
+    pub async fn create_collection(&self, name: &str, is_public: bool) -> Result<CollectionDetail, ClientError> {
+        self.send("POST", "/collections", Some(&CreateCollection { name, is_public })).await
+    }
+
+    pub async fn delete_collection(&self, slug: &str) -> Result<(), ClientError> {
+        self.fetch("DELETE", &format!("/collections/{}", encode_component(slug)), None).await.map(|_| ())
+    }
+
+    pub async fn add_to_collection(&self, slug: &str, quote_id: &str) -> Result<CollectionDetail, ClientError> {
+        let path = format!("/collections/{}/quotes", encode_component(slug));
+        self.send("POST", &path, Some(&AddToCollection { quote_id })).await
+    }
+
+    pub async fn remove_from_collection(&self, slug: &str, quote_id: &str) -> Result<CollectionDetail, ClientError> {
+        let path = format!("/collections/{}/quotes/{}", encode_component(slug), encode_component(quote_id));
+        self.send("DELETE", &path, None::<&()>).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        self.send("GET", path, None::<&()>).await
+    }
+
+    /// A GET whose response is saved, and replayed when the server is
+    /// unreachable.
+    async fn get_saved<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        self.get_saved_page(path).await.map(|(value, _)| value)
+    }
+
+    /// `get_saved`, along with the response's next-page cursor. Only the
+    /// body is saved, so a replayed response has none.
+    async fn get_saved_page<T: DeserializeOwned>(&self, path: &str) -> Result<(T, Option<String>), ClientError> {
+        let fetched = match self.fetch("GET", path, None).await {
+            Ok(resp) => {
+                let next_cursor = next_cursor(&resp);
+                response_text(resp).await.map(|text| (text, next_cursor))
+            }
+            Err(e) => Err(e),
+        };
+
+        match fetched {
+            Ok((text, next_cursor)) => {
+                let value = decode("GET", path, &text)?;
+                save_response(path.to_string(), text);
+                Ok((value, next_cursor))
+            }
+            Err(e) if e.is_unreachable() => match offline::saved_response(path).await {
+                Ok(Some(text)) => Ok((decode("GET", path, &text)?, None)),
+                _ => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn send<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&B>
+    ) -> Result<T, ClientError> {
+        let body = body
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| ClientError::Decode(e.to_string()))?;
+        let resp = self.fetch(method, path, body).await?;
+        decode(method, path, &response_text(resp).await?)
+    }
+
+    /// Sends a request identified by this browser's client id, which the
+    /// endpoints that keep per-client state (likes, collections) need, and
+    /// fails on any non-2xx status.
+    async fn fetch(&self, method: &str, path: &str, body: Option<String>) -> Result<Response, ClientError> {
+        let url = format!("{}{}", self.base, path);
+
+        let opts = RequestInit::new();
+        opts.set_method(method);
+        opts.set_mode(RequestMode::Cors);
+
+        let headers = Headers::new()?;
+        headers.set("X-Client-Id", &client_id())?;
+        if let Some(body) = body {
+            headers.set("Content-Type", "application/json")?;
+            opts.set_body(&JsValue::from_str(&body));
+        }
+        opts.set_headers(&headers);
+
+        let request = Request::new_with_str_and_init(&url, &opts)?;
+        let window = window().ok_or_else(|| ClientError::Network("no window".to_string()))?;
+        let result: Result<Response, ClientError> = async {
+            let resp: Response = JsFuture::from(window.fetch_with_request(&request)).await?.dyn_into()?;
+            if !resp.ok() {
+                return Err(ClientError::Http {
+                    status: resp.status(),
+                    method: method.to_string(),
+                    path: path.to_string(),
+                });
+            }
+            Ok(resp)
+        }.await;
+
+        set_reachable(!result.as_ref().is_err_and(ClientError::is_unreachable));
+        result
+    }
+}
+
+async fn response_text(resp: Response) -> Result<String, ClientError> {
+    Ok(JsFuture::from(resp.text()?).await?.as_string().unwrap_or_default())
+}
+
+fn next_cursor(resp: &Response) -> Option<String> {
+    resp.headers().get(NEXT_CURSOR_HEADER).ok().flatten()
+}
+
+fn decode<T: DeserializeOwned>(method: &str, path: &str, text: &str) -> Result<T, ClientError> {
+    serde_json::from_str(text).map_err(|e| ClientError::Decode(format!("{} {}: {}", method, path, e)))
+}
+
+/// Saving happens in the background; a full or unavailable IndexedDB only
+/// costs the offline copy.
+fn save_response(path: String, json: String) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = offline::save_response(&path, &json).await;
+    });
+}
@@ -1,19 +1,33 @@
+mod client;
+mod listeners;
+mod offline;
+mod protocol;
+mod views;
+
+use client::{ ApiClient, ClientError, QuoteListQuery, QuoteSort };
+use listeners::{ listen, Scope };
+use offline::PendingQuote;
+use protocol::{ ClientMessage, ModerationAction, ServerMessage };
+use std::{ cell::{ Cell, RefCell }, rc::Rc };
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{
     console,
     window,
+    Element,
     HtmlElement,
     HtmlInputElement,
+    HtmlSelectElement,
     HtmlTextAreaElement,
-    Request,
-    RequestInit,
-    RequestMode,
-    Headers,
-    Response,
-    Event,
+    IntersectionObserver,
+    IntersectionObserverEntry,
+    IntersectionObserverInit,
+    EventSource,
+    MessageEvent,
+    WebSocket,
 };
 use serde::{ Deserialize, Serialize };
+use unicode_segmentation::UnicodeSegmentation;
 
 macro_rules! log {
     ($($t:tt)*) => {
@@ -21,130 +35,676 @@ macro_rules! log {
     };
 }
 
+thread_local! {
+    /// Identifies this page load to `/quotes/random`, which then walks a
+    /// shuffled deck instead of picking independently each time.
+    static RANDOM_SESSION: String = random_id();
+
+    /// The `/ws` connection, while open.
+    static LIVE_SOCKET: RefCell<Option<WebSocket>> = const { RefCell::new(None) };
+    /// The quote open in the detail modal, re-announced after a reconnect.
+    static VIEWING: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// The manager all UI functions share; see `quote_manager`.
+    static MANAGER: RefCell<Option<Rc<QuoteManager>>> = const { RefCell::new(None) };
+    /// The list the quote grid is showing; see `Route`.
+    static LIST_ROUTE: RefCell<Option<Route>> = const { RefCell::new(None) };
+    /// Whether the open quote route was pushed by this page, as opposed to
+    /// being loaded from a link.
+    static OPENED_IN_APP: Cell<bool> = const { Cell::new(false) };
+    /// Whether the last API request got through; see `set_reachable`.
+    static REACHABLE: Cell<bool> = const { Cell::new(true) };
+    /// Quotes waiting in the outbox, as of the last change to it.
+    static PENDING_COUNT: Cell<usize> = const { Cell::new(0) };
+    static SYNCING: Cell<bool> = const { Cell::new(false) };
+    /// The pending search from the toolbar, while the user is still typing.
+    static SEARCH_TIMER: Cell<Option<i32>> = const { Cell::new(None) };
+    /// Where the grid's next page starts; `None` once the last is shown.
+    static NEXT_CURSOR: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// Bumped by each `load_quotes`, so pages that arrive after the list
+    /// changed are dropped.
+    static LIST_GENERATION: Cell<u32> = const { Cell::new(0) };
+    static LOADING_MORE: Cell<bool> = const { Cell::new(false) };
+    /// Watches `#list-end`, together with the callback it calls.
+    static LIST_END_OBSERVER: RefCell<Option<(IntersectionObserver, IntersectionCallback)>> = const { RefCell::new(None) };
+}
+
+const CLIENT_ID_KEY: &str = "rusted_quotes_client_id";
+/// Versioned with the server's like fingerprint: likes made under an older
+/// one were dropped, so the hearts remembered for them are too.
+const LIKED_KEY: &str = "rusted_quotes_liked_v1";
+/// Set by moderators (e.g. from the dev console) to receive moderation
+/// notices over the live connection.
+const ADMIN_TOKEN_KEY: &str = "rusted_quotes_admin_token";
+const LIVE_RECONNECT_MS: i32 = 3000;
+/// `<meta name="rusted-quotes-api" content="https://quotes.example.com/api">`
+/// points a deployed bundle at its API without rebuilding.
+const API_META_NAME: &str = "rusted-quotes-api";
+/// Under `trunk serve` the page and the API run side by side on these ports.
+const DEV_SERVER_PORT: &str = "8080";
+const DEV_API_PORT: &str = "3000";
+const FALLBACK_API_BASE: &str = "http://localhost:3000/api";
+/// Cards show this many characters (graphemes) before "Show more";
+/// `data-preview-length` on `#quotes-container` overrides it.
+const DEFAULT_PREVIEW_LENGTH: usize = 150;
+/// The page sizes the toolbar offers; `GET /quotes` returns 10 by default.
+const PAGE_SIZES: [u32; 4] = [10, 20, 50, 100];
+const DEFAULT_PAGE_SIZE: u32 = 10;
+const SEARCH_DEBOUNCE_MS: i32 = 300;
+/// The next page starts loading when the end of the grid is this close to
+/// the bottom of the viewport.
+const LIST_END_MARGIN_PX: f64 = 600.0;
+
+/// Called with the `IntersectionObserverEntry` list.
+type IntersectionCallback = Closure<dyn FnMut(js_sys::Array)>;
+
+/// 128 random bits as hex.
+fn random_id() -> String {
+    (0..4)
+        .map(|_| format!("{:08x}", (js_sys::Math::random() * (u32::MAX as f64)) as u32))
+        .collect()
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    window()?.local_storage().ok().flatten()
+}
+
+/// Stable anonymous id sent as `X-Client-Id`, which the server keys this
+/// browser's likes and collections on.
+fn client_id() -> String {
+    let storage = local_storage();
+    if let Some(id) = storage.as_ref().and_then(|s| s.get_item(CLIENT_ID_KEY).ok().flatten()) {
+        return id;
+    }
+
+    let id = random_id();
+    if let Some(storage) = storage {
+        let _ = storage.set_item(CLIENT_ID_KEY, &id);
+    }
+    id
+}
+
+fn liked_ids() -> Vec<String> {
+    local_storage()
+        .and_then(|storage| storage.get_item(LIKED_KEY).ok().flatten())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn remember_like(id: &str, liked: bool) {
+    let mut ids = liked_ids();
+    ids.retain(|existing| existing != id);
+    if liked {
+        ids.push(id.to_string());
+    }
+
+    if let (Some(storage), Ok(json)) = (local_storage(), serde_json::to_string(&ids)) {
+        let _ = storage.set_item(LIKED_KEY, &json);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Quote {
     pub id: String,
     pub quote: String,
     pub author: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub like_count: u32,
+    #[serde(default)]
+    pub source: Option<QuoteSource>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub translation_of: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuoteSource {
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub url: Option<String>,
+    pub page: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-pub struct CreateQuote {
-    pub quote: String,
-    pub author: Option<String>,
+pub struct Citation {
+    pub id: String,
+    pub style: String,
+    pub citation: String,
+    pub verified: bool,
+}
+
+/// A change pushed by `/quotes/stream`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum QuoteEvent {
+    Created {
+        quote: Quote,
+    },
+    Updated {
+        quote: Quote,
+    },
+    Deleted {
+        id: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LikeStatus {
+    pub id: String,
+    pub like_count: u32,
+    pub liked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Collection {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_public: bool,
+    pub quote_count: i64,
+    pub owned: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A collection with its quotes, as returned by the endpoints that change it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionDetail {
+    #[serde(flatten)]
+    pub collection: Collection,
+    #[serde(default)]
+    pub quotes: Vec<Quote>,
+}
+
+/// Where the API lives, in order of precedence: the `rusted-quotes-api`
+/// `<meta>` tag, the `RUSTED_QUOTES_API` variable at build time, then the
+/// page's own origin. Relative values are resolved against the page.
+fn detect_api_base() -> String {
+    let Some(win) = window() else {
+        return FALLBACK_API_BASE.to_string();
+    };
+    let location = win.location();
+    let origin = location.origin().unwrap_or_default();
+
+    let configured = win
+        .document()
+        .and_then(|document| document.query_selector(&format!("meta[name=\"{}\"]", API_META_NAME)).ok().flatten())
+        .and_then(|meta| meta.get_attribute("content"))
+        .or_else(|| option_env!("RUSTED_QUOTES_API").map(str::to_string))
+        .map(|base| base.trim().to_string())
+        .filter(|base| !base.is_empty());
+
+    match configured {
+        Some(base) if base.starts_with('/') => format!("{}{}", origin, base),
+        Some(base) => base,
+        // Pages opened from disk have no usable origin.
+        None if !origin.starts_with("http") => FALLBACK_API_BASE.to_string(),
+        None if location.port().unwrap_or_default() == DEV_SERVER_PORT => {
+            let protocol = location.protocol().unwrap_or_else(|_| "http:".to_string());
+            let hostname = location.hostname().unwrap_or_else(|_| "localhost".to_string());
+            format!("{}//{}:{}/api", protocol, hostname, DEV_API_PORT)
+        }
+        None => format!("{}/api", origin),
+    }
+}
+
+/// The manager shared by every UI function, created on first use.
+fn quote_manager() -> Rc<QuoteManager> {
+    MANAGER.with(|manager| manager.borrow_mut().get_or_insert_with(|| Rc::new(QuoteManager::new())).clone())
 }
 
 #[wasm_bindgen]
 pub struct QuoteManager {
-    api_base: String,
+    client: ApiClient,
+}
+
+impl Default for QuoteManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hands a typed result to JavaScript as the plain JSON the API returned.
+fn to_js<T: Serialize>(result: Result<T, ClientError>) -> Result<JsValue, JsValue> {
+    let value = result?;
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
 }
 
+/// The JavaScript API. Rust code uses `client()` and gets typed results.
 #[wasm_bindgen]
 impl QuoteManager {
+    /// A manager for the API this page was configured for; see
+    /// `detect_api_base`.
     #[wasm_bindgen(constructor)]
     pub fn new() -> QuoteManager {
+        QuoteManager::with_base_url(&detect_api_base())
+    }
+
+    /// A manager for the API at `base_url`, e.g. `https://quotes.example.com/api`.
+    #[wasm_bindgen]
+    pub fn with_base_url(base_url: &str) -> QuoteManager {
         QuoteManager {
-            api_base: "http://localhost:3000/api".to_string(),
+            client: ApiClient::new(base_url),
         }
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn api_base(&self) -> String {
+        self.client.base().to_string()
+    }
+
+    /// All filters are optional, so `fetch_quotes()` lists everything.
     #[wasm_bindgen]
-    pub async fn fetch_quotes(&self) -> Result<JsValue, JsValue> {
-        let url = format!("{}/quotes", self.api_base);
+    pub async fn fetch_quotes(
+        &self,
+        search: Option<String>,
+        tag: Option<String>,
+        author: Option<String>
+    ) -> Result<JsValue, JsValue> {
+        to_js(self.client.quotes(&QuoteListQuery { search, tag, author, ..Default::default() }).await)
+    }
 
-        let opts = RequestInit::new();
-        opts.set_method("GET");
-        opts.set_mode(RequestMode::Cors);
+    #[wasm_bindgen]
+    pub async fn create_quote(&self, quote: &str, author: Option<String>) -> Result<JsValue, JsValue> {
+        to_js(self.client.create_quote(quote, author.as_deref(), None).await)
+    }
 
-        let request = Request::new_with_str_and_init(&url, &opts)?;
+    #[wasm_bindgen]
+    pub async fn get_random_quote(&self, session: Option<String>) -> Result<JsValue, JsValue> {
+        to_js(self.client.random_quote(session.as_deref()).await)
+    }
 
-        let window = window().unwrap();
-        let resp_value = window.fetch_with_request(&request);
-        let resp: Response = JsFuture::from(resp_value).await?.dyn_into()?;
+    #[wasm_bindgen]
+    pub async fn get_daily_quote(&self) -> Result<JsValue, JsValue> {
+        to_js(self.client.daily_quote().await)
+    }
 
-        let json = JsFuture::from(resp.json()?).await?;
-        Ok(json)
+    #[wasm_bindgen]
+    pub async fn like_quote(&self, id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.like(id).await)
     }
 
     #[wasm_bindgen]
-    pub async fn create_quote(
-        &self,
-        quote: &str,
-        author: Option<String>
-    ) -> Result<JsValue, JsValue> {
-        let url = format!("{}/quotes", self.api_base);
+    pub async fn unlike_quote(&self, id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.unlike(id).await)
+    }
 
-        let create_quote = CreateQuote {
-            quote: quote.to_string(),
-            author,
-        };
+    /// The other language versions of a quote, original first.
+    #[wasm_bindgen]
+    pub async fn get_translations(&self, id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.translations(id).await)
+    }
 
-        let body_json = serde_json
-            ::to_string(&create_quote)
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    /// `style` is one of `apa`, `mla` or `chicago`.
+    #[wasm_bindgen]
+    pub async fn get_citation(&self, id: &str, style: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.citation(id, style).await)
+    }
 
-        let opts = RequestInit::new();
-        opts.set_method("POST");
-        opts.set_mode(RequestMode::Cors);
+    #[wasm_bindgen]
+    pub async fn list_collections(&self, mine: bool) -> Result<JsValue, JsValue> {
+        to_js(self.client.collections(mine).await)
+    }
 
-        let body_js = JsValue::from_str(&body_json);
-        opts.set_body(&body_js);
+    #[wasm_bindgen]
+    pub async fn get_collection(&self, slug: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.collection(slug).await)
+    }
 
-        let headers = Headers::new()?;
-        headers.set("Content-Type", "application/json")?;
-        opts.set_headers(&headers);
+    #[wasm_bindgen]
+    pub async fn create_collection(&self, name: &str, is_public: bool) -> Result<JsValue, JsValue> {
+        to_js(self.client.create_collection(name, is_public).await)
+    }
 
-        let request = Request::new_with_str_and_init(&url, &opts)?;
+    #[wasm_bindgen]
+    pub async fn delete_collection(&self, slug: &str) -> Result<JsValue, JsValue> {
+        self.client.delete_collection(slug).await?;
+        Ok(JsValue::NULL)
+    }
 
-        let window = window().unwrap();
-        let resp_value = window.fetch_with_request(&request);
-        let resp: Response = JsFuture::from(resp_value).await?.dyn_into()?;
+    #[wasm_bindgen]
+    pub async fn add_to_collection(&self, slug: &str, quote_id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.add_to_collection(slug, quote_id).await)
+    }
 
-        let json = JsFuture::from(resp.json()?).await?;
-        Ok(json)
+    #[wasm_bindgen]
+    pub async fn remove_from_collection(&self, slug: &str, quote_id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.remove_from_collection(slug, quote_id).await)
     }
 
     #[wasm_bindgen]
-    pub async fn get_random_quote(&self) -> Result<JsValue, JsValue> {
-        let url = format!("{}/quotes/random", self.api_base);
+    pub async fn get_quote_by_id(&self, id: &str) -> Result<JsValue, JsValue> {
+        to_js(self.client.quote(id).await)
+    }
+}
 
-        let opts = RequestInit::new();
-        opts.set_method("GET");
-        opts.set_mode(RequestMode::Cors);
+impl QuoteManager {
+    fn client(&self) -> &ApiClient {
+        &self.client
+    }
 
-        let request = Request::new_with_str_and_init(&url, &opts)?;
+    fn stream_url(&self) -> String {
+        format!("{}/quotes/stream", self.client.base())
+    }
 
-        let window = window().unwrap();
-        let resp_value = window.fetch_with_request(&request);
-        let resp: Response = JsFuture::from(resp_value).await?.dyn_into()?;
+    /// The server's share page, which unfurls with a rendered quote card.
+    fn share_url(&self, id: &str) -> String {
+        format!("{}/share/{}", self.client.base().trim_end_matches("/api"), encode_component(id))
+    }
 
-        let json = JsFuture::from(resp.json()?).await?;
-        Ok(json)
+    fn live_url(&self) -> String {
+        let base = self.client
+            .base()
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+        format!("{}/ws", base)
     }
+}
 
-    #[wasm_bindgen]
-pub async fn get_quote_by_id(&self, id: &str) -> Result<JsValue, JsValue> {
-    let url = format!("{}/quotes/{}", self.api_base, id);
+/// A view of the page, kept in the URL fragment (`#/quotes/:id`) so it can
+/// be linked to and the back button works on any static host.
+#[derive(Debug, Clone, PartialEq)]
+enum Route {
+    /// The quote grid, as set up by the toolbar: `#/?search=…&sort=…`, or
+    /// `#/authors/:name` and `#/tags/:tag` for a list filtered by just that.
+    /// Authors have no ids of their own; the name is the id.
+    Quotes(QuoteListQuery),
+    Quote(String),
+}
 
-    let opts = RequestInit::new();
-    opts.set_method("GET");
-    opts.set_mode(RequestMode::Cors);
+/// Reverses `encode_component`, also reading `+` as a space. Text that does
+/// not decode to UTF-8 is kept as it is.
+fn decode_component(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| text.to_string())
+}
+
+/// Percent-encodes `text` like JavaScript's `encodeURIComponent`.
+fn encode_component(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' =>
+                encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+impl Route {
+    fn home() -> Route {
+        Route::Quotes(QuoteListQuery::default())
+    }
+
+    fn parse(fragment: &str) -> Route {
+        let fragment = fragment.trim_start_matches('#');
+        let (path, query) = fragment.split_once('?').unwrap_or((fragment, ""));
+        let segments: Vec<String> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(decode_component)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let mut list = QuoteListQuery::default();
+        match segments.as_slice() {
+            ["quotes", id] => {
+                return Route::Quote(id.to_string());
+            }
+            ["authors", name] => list.author = Some(name.to_string()),
+            ["tags", tag] => list.tag = Some(tag.to_string()),
+            _ => {}
+        }
+
+        for pair in query.split('&') {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = decode_component(value).trim().to_string();
+            if value.is_empty() {
+                continue;
+            }
+            match name {
+                // `q` is what `#/search?q=…` links used before the toolbar.
+                "search" | "q" => list.search = Some(value),
+                "author" => list.author = Some(value),
+                "tag" => list.tag = Some(value),
+                "sort" => list.sort = QuoteSort::parse(&value).unwrap_or_default(),
+                "limit" => list.limit = value.parse().ok().filter(|limit| PAGE_SIZES.contains(limit)),
+                _ => {}
+            }
+        }
+        Route::Quotes(list)
+    }
+
+    fn fragment(&self) -> String {
+        match self {
+            Route::Quote(id) => format!("#/quotes/{}", encode_component(id)),
+            Route::Quotes(list) => {
+                let mut rest = list.clone();
+                let path = match (&list.author, &list.tag) {
+                    (Some(name), None) => {
+                        rest.author = None;
+                        format!("#/authors/{}", encode_component(name))
+                    }
+                    (None, Some(tag)) => {
+                        rest.tag = None;
+                        format!("#/tags/{}", encode_component(tag))
+                    }
+                    _ => "#/".to_string(),
+                };
+                format!("{}{}", path, rest.query_string())
+            }
+        }
+    }
+
+    /// What the quote grid shows; `None` for routes that are a modal over
+    /// whatever list was open.
+    fn list_query(&self) -> Option<QuoteListQuery> {
+        match self {
+            Route::Quotes(list) => Some(list.clone()),
+            Route::Quote(_) => None,
+        }
+    }
+}
+
+fn current_route() -> Route {
+    Route::parse(&window().and_then(|win| win.location().hash().ok()).unwrap_or_default())
+}
 
-    let request = Request::new_with_str_and_init(&url, &opts)?;
+/// The list route the grid is showing, or the unfiltered list before the
+/// first load.
+fn list_route() -> Route {
+    LIST_ROUTE.with(|route| route.borrow().clone()).unwrap_or_else(Route::home)
+}
 
-    let window = window().unwrap();
-    let resp_value = window.fetch_with_request(&request);
-    let resp: Response = JsFuture::from(resp_value).await?.dyn_into()?;
+/// Moves to `route` with a history entry; the `hashchange` that follows
+/// applies it. Switching from one quote to another replaces the entry, so
+/// back always returns to the list.
+fn navigate(route: Route) {
+    let location = window().unwrap().location();
+    let current = current_route();
+    if current == route {
+        apply_route(route);
+        return;
+    }
 
-    if resp.ok() {
-        let json = JsFuture::from(resp.json()?).await?;
-        Ok(json)
+    if matches!((&current, &route), (Route::Quote(_), Route::Quote(_))) {
+        let _ = location.replace(&route.fragment());
     } else {
-        Err(JsValue::from_str(&format!("HTTP {}: Quote not found", resp.status())))
+        OPENED_IN_APP.with(|opened| opened.set(matches!(route, Route::Quote(_))));
+        let _ = location.set_hash(&route.fragment());
+    }
+}
+
+fn setup_router() {
+    let win = window().unwrap();
+    listen(Scope::Page, &win, "hashchange", |_| apply_route(current_route()));
+
+    apply_route(current_route());
+}
+
+fn apply_route(route: Route) {
+    match route {
+        Route::Quote(id) => {
+            if LIST_ROUTE.with(|route| route.borrow().is_none()) {
+                show_list(Route::home());
+            }
+            let viewing = VIEWING.with(|viewing| viewing.borrow().clone());
+            if viewing.as_deref() != Some(id.as_str()) {
+                show_quote_detail(&id);
+            }
+        }
+        route => {
+            hide_modal();
+            OPENED_IN_APP.with(|opened| opened.set(false));
+            if LIST_ROUTE.with(|current| current.borrow().as_ref() != Some(&route)) {
+                show_list(route);
+            }
+        }
+    }
+}
+
+fn show_list(route: Route) {
+    if let Some(list) = route.list_query() {
+        render_toolbar(&list);
+    }
+
+    LIST_ROUTE.with(|current| *current.borrow_mut() = Some(route));
+    load_quotes();
+}
+
+/// Changes the list the grid shows. The change replaces the current history
+/// entry rather than adding one, so back leaves the list instead of
+/// replaying every keystroke in the search box.
+fn update_list(change: impl FnOnce(&mut QuoteListQuery)) {
+    let Some(mut list) = list_route().list_query() else {
+        return;
+    };
+    change(&mut list);
+
+    let route = Route::Quotes(list);
+    if route != list_route() {
+        let _ = window().unwrap().location().replace(&route.fragment());
+    }
+}
+
+fn setup_toolbar() {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(input) = document.get_element_by_id("search-input") {
+        let input: HtmlInputElement = input.dyn_into().unwrap();
+        let target = input.clone();
+        listen(Scope::Page, &input, "input", move |_| {
+            let win = window().unwrap();
+            if let Some(timer) = SEARCH_TIMER.with(|timer| timer.take()) {
+                win.clear_timeout_with_handle(timer);
+            }
+
+            let target = target.clone();
+            let search = Closure::once_into_js(move || {
+                SEARCH_TIMER.with(|timer| timer.set(None));
+                let value = target.value().trim().to_string();
+                update_list(|list| list.search = (!value.is_empty()).then_some(value));
+            });
+            let timer = win.set_timeout_with_callback_and_timeout_and_arguments_0(
+                search.unchecked_ref(),
+                SEARCH_DEBOUNCE_MS
+            );
+            SEARCH_TIMER.with(|current| current.set(timer.ok()));
+        });
+    }
+
+    if let Some(select) = document.get_element_by_id("sort-select") {
+        let select: HtmlSelectElement = select.dyn_into().unwrap();
+        let target = select.clone();
+        listen(Scope::Page, &select, "change", move |_| {
+            let sort = QuoteSort::parse(&target.value()).unwrap_or_default();
+            update_list(|list| list.sort = sort);
+        });
+    }
+
+    if let Some(select) = document.get_element_by_id("page-size-select") {
+        let select: HtmlSelectElement = select.dyn_into().unwrap();
+        let target = select.clone();
+        listen(Scope::Page, &select, "change", move |_| {
+            let limit = target.value().parse().ok().filter(|&limit| limit != DEFAULT_PAGE_SIZE);
+            update_list(|list| list.limit = limit);
+        });
+    }
+}
+
+/// Brings the toolbar in line with `list`, e.g. after a link or the back
+/// button changed the route.
+fn render_toolbar(list: &QuoteListQuery) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(input) = document.get_element_by_id("search-input") {
+        let input: HtmlInputElement = input.dyn_into().unwrap();
+        // Left alone while it already says the same, so typing is not
+        // disturbed by the route it just produced.
+        let search = list.search.as_deref().unwrap_or("");
+        if input.value().trim() != search {
+            input.set_value(search);
+        }
+    }
+    if let Some(select) = document.get_element_by_id("sort-select") {
+        select.dyn_into::<HtmlSelectElement>().unwrap().set_value(list.sort.as_str());
+    }
+    if let Some(select) = document.get_element_by_id("page-size-select") {
+        let limit = list.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        select.dyn_into::<HtmlSelectElement>().unwrap().set_value(&limit.to_string());
+    }
+    if let Some(chips) = document.get_element_by_id("filter-chips") {
+        chips.set_inner_html(&views::filter_chips(list).into_string());
+        if list.author.is_some() || list.tag.is_some() {
+            chips.class_list().remove_1("hidden").unwrap();
+        } else {
+            chips.class_list().add_1("hidden").unwrap();
+        }
     }
 }
+
+/// Leaves a quote route after its modal is dismissed: back if we opened it,
+/// so the history has no dead entry, otherwise over to the list.
+fn leave_quote_route() {
+    let win = window().unwrap();
+    if OPENED_IN_APP.with(|opened| opened.replace(false)) {
+        if let Ok(history) = win.history() {
+            let _ = history.back();
+            return;
+        }
+    }
+    let _ = win.location().replace(&list_route().fragment());
 }
 
 #[wasm_bindgen(start)]
@@ -164,39 +724,28 @@ pub fn init_app() {
 
     setup_modal();
     setup_form_handlers();
-    load_quotes();
+    setup_toolbar();
+    setup_quote_grid();
+    setup_infinite_scroll();
+    load_daily_quote();
+    setup_router();
+    subscribe_to_quote_stream();
+    connect_live_socket();
+    setup_offline_support();
 }
 
 fn setup_modal() {
     let win = window().unwrap();
     let document = win.document().unwrap();
 
-    if let Some(modal_close) = document.get_element_by_id("modal-close") {
-        let modal_close: HtmlElement = modal_close.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move || {
-                close_modal();
-            }) as Box<dyn Fn()>
-        );
-
-        modal_close.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-    }
-
     if let Some(modal_bg) = document.get_element_by_id("quote-modal") {
-        let modal_bg: HtmlElement = modal_bg.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move |event: Event| {
-                if let Some(target) = event.target() {
-                    if target == event.current_target().unwrap() {
-                        close_modal();
-                    }
+        listen(Scope::Page, &modal_bg, "click", |event| {
+            if let Some(target) = event.target() {
+                if target == event.current_target().unwrap() {
+                    close_modal();
                 }
-            }) as Box<dyn Fn(Event)>
-        );
-
-        modal_bg.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+            }
+        });
     }
 }
 
@@ -205,27 +754,11 @@ fn setup_form_handlers() {
     let document = win.document().unwrap();
 
     if let Some(add_btn) = document.get_element_by_id("add-quote-btn") {
-        let add_btn: HtmlElement = add_btn.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move || {
-                show_add_quote_form();
-            }) as Box<dyn Fn()>
-        );
-
-        add_btn.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+        listen(Scope::Page, &add_btn, "click", |_| show_add_quote_form());
     }
 
     if let Some(random_btn) = document.get_element_by_id("random-quote-btn") {
-        let random_btn: HtmlElement = random_btn.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move || {
-                get_random_quote();
-            }) as Box<dyn Fn()>
-        );
-
-        random_btn.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+        listen(Scope::Page, &random_btn, "click", |_| get_random_quote());
     }
 }
 
@@ -235,70 +768,8 @@ fn show_add_quote_form() {
 
     if let Some(modal) = document.get_element_by_id("quote-modal") {
         if let Some(modal_content) = document.get_element_by_id("modal-content") {
-            modal_content.set_inner_html(
-                r#"
-                <div class="glass-morphism rounded-3xl p-8 cyber-border max-w-md w-full mx-auto transform transition-all">
-                    <div class="flex justify-between items-center mb-8">
-                        <div>
-                            <h3 class="text-3xl font-bold text-white mb-2">Add New Quote</h3>
-                            <p class="text-gray-400 font-mono text-sm">{ create_wisdom() }</p>
-                        </div>
-                        <button id="modal-close" class="text-gray-400 hover:text-neon-cyan transition-colors p-2 rounded-lg hover:bg-dark-800">
-                            <svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                                <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12"></path>
-                            </svg>
-                        </button>
-                    </div>
-                    
-                    <form id="quote-form" class="space-y-6">
-                        <div>
-                            <label class="flex items-center gap-2 text-sm font-bold text-neon-cyan mb-3 font-mono">
-                                <svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20">
-                                    <path fill-rule="evenodd" d="M18 13V5a2 2 0 00-2-2H4a2 2 0 00-2 2v8a2 2 0 002 2h3l3 3 3-3h3a2 2 0 002-2zM5 7a1 1 0 011-1h8a1 1 0 110 2H6a1 1 0 01-1-1zm1 3a1 1 0 100 2h3a1 1 0 100-2H6z" clip-rule="evenodd" />
-                                </svg>
-                                QUOTE_TEXT
-                            </label>
-                            <textarea 
-                                id="quote-input" 
-                                rows="4" 
-                                class="w-full px-4 py-4 bg-dark-900 border border-dark-700 rounded-xl focus:ring-2 focus:ring-neon-cyan focus:border-transparent resize-none text-gray-100 placeholder-gray-500 transition-all font-primary"
-                                placeholder="Enter your inspiring quote..."
-                                required
-                            ></textarea>
-                        </div>
-                        
-                        <div>
-                            <label class="flex items-center gap-2 text-sm font-bold text-neon-purple mb-3 font-mono">
-                                <svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20">
-                                    <path fill-rule="evenodd" d="M10 9a3 3 0 100-6 3 3 0 000 6zm-7 9a7 7 0 1114 0H3z" clip-rule="evenodd" />
-                                </svg>
-                                AUTHOR (Optional)
-                            </label>
-                            <input 
-                                type="text" 
-                                id="author-input" 
-                                class="w-full px-4 py-4 bg-dark-900 border border-dark-700 rounded-xl focus:ring-2 focus:ring-neon-purple focus:border-transparent text-gray-100 placeholder-gray-500 transition-all font-primary"
-                                placeholder="Author name"
-                            >
-                        </div>
-                        
-                        <div class="flex gap-4 pt-4">
-                            <button type="submit" class="flex-1 bg-gradient-to-r from-neon-purple to-neon-cyan text-dark-950 font-bold py-4 px-6 rounded-xl hover:from-neon-cyan hover:to-neon-purple transition-all transform hover:scale-105 shadow-lg hover:shadow-neon-cyan/25">
-                                <span class="flex items-center justify-center gap-2">
-                                    <svg class="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                                        <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 6v6m0 0v6m0-6h6m-6 0H6" />
-                                    </svg>
-                                    Deploy Quote
-                                </span>
-                            </button>
-                            <button type="button" id="cancel-btn" class="flex-1 bg-dark-800 border border-dark-600 text-gray-300 font-bold py-4 px-6 rounded-xl hover:bg-dark-700 hover:border-dark-500 transition-all">
-                                Cancel
-                            </button>
-                        </div>
-                    </form>
-                </div>
-                "#
-            );
+            listeners::clear(Scope::Modal);
+            modal_content.set_inner_html(&views::add_quote_form().into_string());
 
             let modal: HtmlElement = modal.dyn_into().unwrap();
             modal.class_list().remove_1("hidden").unwrap();
@@ -313,40 +784,16 @@ fn setup_add_quote_form() {
     let document = win.document().unwrap();
 
     if let Some(form) = document.get_element_by_id("quote-form") {
-        let form: HtmlElement = form.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move |event: Event| {
-                event.prevent_default();
-                submit_quote_form();
-            }) as Box<dyn Fn(Event)>
-        );
-
-        form.set_onsubmit(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
-    }
-
-    if let Some(cancel_btn) = document.get_element_by_id("cancel-btn") {
-        let cancel_btn: HtmlElement = cancel_btn.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move || {
-                close_modal();
-            }) as Box<dyn Fn()>
-        );
-
-        cancel_btn.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+        listen(Scope::Modal, &form, "submit", |event| {
+            event.prevent_default();
+            submit_quote_form();
+        });
     }
 
-    if let Some(modal_close) = document.get_element_by_id("modal-close") {
-        let modal_close: HtmlElement = modal_close.dyn_into().unwrap();
-        let closure = Closure::wrap(
-            Box::new(move || {
-                close_modal();
-            }) as Box<dyn Fn()>
-        );
-
-        modal_close.set_onclick(Some(closure.as_ref().unchecked_ref()));
-        closure.forget();
+    for id in ["cancel-btn", "modal-close"] {
+        if let Some(button) = document.get_element_by_id(id) {
+            listen(Scope::Modal, &button, "click", |_| close_modal());
+        }
     }
 }
 
@@ -364,146 +811,414 @@ fn submit_quote_form() {
         return;
     }
 
-    let author_opt = if author.trim().is_empty() { None } else { Some(author) };
+    let pending = PendingQuote {
+        submission_id: random_id(),
+        quote,
+        author: if author.trim().is_empty() { None } else { Some(author) },
+        queued_at: String::from(js_sys::Date::new_0().to_iso_string()),
+    };
 
     wasm_bindgen_futures::spawn_local(async move {
-        let manager = QuoteManager::new();
-        match manager.create_quote(&quote, author_opt).await {
+        match post_pending(&pending).await {
             Ok(_) => {
                 close_modal();
                 load_quotes();
             }
+            Err(e) if e.is_unreachable() => match offline::queue_submission(&pending).await {
+                Ok(()) => {
+                    close_modal();
+                    PENDING_COUNT.with(|count| count.set(count.get() + 1));
+                    render_connectivity();
+                    show_toast("You're offline. Your quote will be posted when the connection is back.");
+                }
+                Err(e) => {
+                    log!("Error queueing quote: {:?}", e);
+                }
+            },
             Err(e) => {
-                log!("Error creating quote: {:?}", e);
+                log!("Error creating quote: {}", e);
             }
         }
     });
 }
 
-fn close_modal() {
+async fn post_pending(pending: &PendingQuote) -> Result<Quote, ClientError> {
+    quote_manager()
+        .client()
+        .create_quote(&pending.quote, pending.author.as_deref(), Some(&pending.submission_id)).await
+}
+
+fn setup_offline_support() {
     let win = window().unwrap();
-    let document = win.document().unwrap();
 
-    if let Some(modal) = document.get_element_by_id("quote-modal") {
-        let modal: HtmlElement = modal.dyn_into().unwrap();
-        modal.class_list().add_1("hidden").unwrap();
+    // Retry as soon as the browser thinks it is back online, not only on
+    // the next request that happens to get through.
+    listen(Scope::Page, &win, "online", |_| sync_outbox());
+
+    sync_outbox();
+    register_service_worker();
+}
+
+fn register_service_worker() {
+    let navigator = window().unwrap().navigator();
+    if js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker")).unwrap_or(false) {
+        let registration = navigator.service_worker().register("sw.js");
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = JsFuture::from(registration).await {
+                log!("Service worker registration failed: {:?}", e);
+            }
+        });
     }
 }
 
-fn load_quotes() {
-    wasm_bindgen_futures::spawn_local(async move {
-        let manager = QuoteManager::new();
-        match manager.fetch_quotes().await {
-            Ok(quotes_js) => {
-                let quotes: Vec<Quote> = serde_wasm_bindgen
-                    ::from_value(quotes_js)
-                    .unwrap_or_default();
-                let quote_count = quotes.len();
-                display_quotes(quotes);
+/// Called by the client after every request. Coming back online flushes
+/// the outbox.
+fn set_reachable(reachable: bool) {
+    if REACHABLE.with(|current| current.replace(reachable)) == reachable {
+        return;
+    }
+    render_connectivity();
+    if reachable {
+        sync_outbox();
+    }
+}
 
-                let win = window().unwrap();
-                let document = win.document().unwrap();
-                if let Some(count_element) = document.get_element_by_id("quote-count") {
-                    count_element.set_text_content(Some(&quote_count.to_string()));
-                }
-            }
-            Err(e) => {
-                log!("Error fetching quotes: {:?}", e);
-                let win = window().unwrap();
-                let document = win.document().unwrap();
-                if let Some(count_element) = document.get_element_by_id("quote-count") {
-                    count_element.set_text_content(Some("0"));
+/// Posts queued quotes in order, stopping at the first that cannot get
+/// through. Quotes the server refuses are dropped rather than retried
+/// forever.
+fn sync_outbox() {
+    if SYNCING.with(|syncing| syncing.replace(true)) {
+        return;
+    }
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let pending = offline::pending_submissions().await.unwrap_or_default();
+        let mut remaining = pending.len();
+        let mut posted = 0;
+
+        for submission in &pending {
+            match post_pending(submission).await {
+                Ok(_) => posted += 1,
+                Err(e) if e.is_unreachable() || matches!(e, ClientError::Http { status: 500.., .. }) => break,
+                Err(e) => {
+                    log!("Dropping queued quote {}: {}", submission.submission_id, e);
                 }
             }
+            let _ = offline::remove_submission(&submission.submission_id).await;
+            remaining -= 1;
         }
-    });
-}
 
-fn get_random_quote() {
-    wasm_bindgen_futures::spawn_local(async move {
-        let manager = QuoteManager::new();
-        match manager.get_random_quote().await {
-            Ok(quote_js) => {
-                let quote: Quote = serde_wasm_bindgen::from_value(quote_js).unwrap();
-                show_quote_modal(&quote);
-            }
-            Err(e) => {
-                log!("Error fetching random quote: {:?}", e);
-                show_error_modal("No quotes available. Add some quotes first!");
-            }
+        PENDING_COUNT.with(|count| count.set(remaining));
+        SYNCING.with(|syncing| syncing.set(false));
+        render_connectivity();
+        if posted > 0 {
+            show_toast(&format!("Posted {} quote(s) written while offline.", posted));
+            load_quotes();
         }
     });
 }
 
-fn show_error_modal(message: &str) {
+fn render_connectivity() {
     let win = window().unwrap();
     let document = win.document().unwrap();
 
-    if let Some(modal) = document.get_element_by_id("quote-modal") {
-        if let Some(modal_content) = document.get_element_by_id("modal-content") {
-            modal_content.set_inner_html(
-                &format!(r#"
-                <div class="glass-morphism rounded-3xl p-8 cyber-border max-w-md w-full mx-auto transform transition-all">
-                    <div class="flex justify-between items-center mb-6">
-                        <div>
-                            <h3 class="text-2xl font-bold text-red-400 mb-2">System Error</h3>
-                            <p class="text-gray-400 font-mono text-sm">{{ error_handler() }}</p>
-                        </div>
-                        <button id="modal-close" class="text-gray-400 hover:text-red-400 transition-colors p-2 rounded-lg hover:bg-dark-800">
-                            <svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24">
-                                <path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12"></path>
-                            </svg>
-                        </button>
-                    </div>
-                    
-                    <div class="text-center space-y-6">
-                        <div class="text-6xl">⚠️</div>
-                        <div class="space-y-3">
-                            <p class="text-gray-300 text-lg">{}</p>
-                            <div class="bg-dark-900 border border-red-500/20 rounded-lg p-4">
-                                <p class="text-red-400 font-mono text-sm">
-                                    ERROR_CODE: CONNECTION_FAILED
-                                </p>
-                            </div>
-                        </div>
-                        <button id="close-error" class="bg-red-500 hover:bg-red-600 text-white font-bold py-3 px-8 rounded-xl transition-all transform hover:scale-105 shadow-lg">
-                            <span class="flex items-center gap-2">
-                                <svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20">
-                                    <path fill-rule="evenodd" d="M16.707 5.293a1 1 0 010 1.414l-8 8a1 1 0 01-1.414 0l-4-4a1 1 0 011.414-1.414L8 12.586l7.293-7.293a1 1 0 011.414 0z" clip-rule="evenodd" />
-                                </svg>
-                                Acknowledge
-                            </span>
-                        </button>
-                    </div>
-                </div>
-                "#, message)
-            );
-
-            let modal: HtmlElement = modal.dyn_into().unwrap();
-            modal.class_list().remove_1("hidden").unwrap();
+    let Some(banner) = document.get_element_by_id("offline-banner") else {
+        return;
+    };
+    let reachable = REACHABLE.with(Cell::get);
+    let pending = PENDING_COUNT.with(Cell::get);
 
-            if let Some(modal_close) = document.get_element_by_id("modal-close") {
-                let modal_close: HtmlElement = modal_close.dyn_into().unwrap();
-                let closure = Closure::wrap(
-                    Box::new(move || {
-                        close_modal();
-                    }) as Box<dyn Fn()>
-                );
-                
-                modal_close.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                closure.forget();
+    let mut parts = Vec::new();
+    if !reachable {
+        parts.push("Offline: showing saved quotes.".to_string());
+    }
+    if pending > 0 {
+        parts.push(format!("{} quote(s) waiting to be posted.", pending));
+    }
+
+    if parts.is_empty() {
+        banner.class_list().add_1("hidden").unwrap();
+    } else {
+        banner.set_text_content(Some(&parts.join(" ")));
+        banner.class_list().remove_1("hidden").unwrap();
+    }
+}
+
+/// Dismisses the modal, leaving its quote route if it has one.
+fn close_modal() {
+    hide_modal();
+    if matches!(current_route(), Route::Quote(_)) {
+        leave_quote_route();
+    }
+}
+
+fn hide_modal() {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(modal) = document.get_element_by_id("quote-modal") {
+        let modal: HtmlElement = modal.dyn_into().unwrap();
+        modal.class_list().add_1("hidden").unwrap();
+    }
+
+    if VIEWING.with(|viewing| viewing.borrow_mut().take()).is_some() {
+        send_live(&ClientMessage::Leave);
+    }
+}
+
+/// Loads the first page of the current list, replacing the grid. Later
+/// pages are appended by `load_more_quotes` as the end of the grid scrolls
+/// into view.
+fn load_quotes() {
+    let generation = LIST_GENERATION.with(|generation| {
+        generation.set(generation.get() + 1);
+        generation.get()
+    });
+    NEXT_CURSOR.with(|cursor| cursor.borrow_mut().take());
+    LOADING_MORE.with(|loading| loading.set(false));
+    render_list_end(views::ListEnd::Hidden);
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        let query = list_route().list_query().unwrap_or_default();
+        let result = manager.client().quotes_page(&query, None).await;
+        if LIST_GENERATION.with(Cell::get) != generation {
+            return;
+        }
+
+        match result {
+            Ok(page) => {
+                let quote_count = page.quotes.len();
+                let has_more = page.next_cursor.is_some();
+                display_quotes(page.quotes);
+                NEXT_CURSOR.with(|cursor| *cursor.borrow_mut() = page.next_cursor);
+
+                let win = window().unwrap();
+                let document = win.document().unwrap();
+                if let Some(count_element) = document.get_element_by_id("quote-count") {
+                    count_element.set_text_content(Some(&quote_count.to_string()));
+                }
+
+                render_list_end(match (has_more, quote_count) {
+                    (true, _) => views::ListEnd::More,
+                    (false, 0) => views::ListEnd::Hidden,
+                    (false, _) => views::ListEnd::End,
+                });
+                if has_more && list_end_in_reach() {
+                    load_more_quotes();
+                }
+            }
+            Err(e) => {
+                log!("Error fetching quotes: {}", e);
+                let win = window().unwrap();
+                let document = win.document().unwrap();
+                if let Some(count_element) = document.get_element_by_id("quote-count") {
+                    count_element.set_text_content(Some("0"));
+                }
             }
+        }
+    });
+}
 
-            if let Some(close_error) = document.get_element_by_id("close-error") {
-                let close_error: HtmlElement = close_error.dyn_into().unwrap();
-                let closure = Closure::wrap(
-                    Box::new(move || {
-                        close_modal();
-                    }) as Box<dyn Fn()>
-                );
-                
-                close_error.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                closure.forget();
+/// Appends the next page of the current list, if there is one and it is not
+/// already on its way.
+fn load_more_quotes() {
+    if LOADING_MORE.with(Cell::get) {
+        return;
+    }
+    let Some(cursor) = NEXT_CURSOR.with(|cursor| cursor.borrow().clone()) else {
+        return;
+    };
+    let generation = LIST_GENERATION.with(Cell::get);
+    LOADING_MORE.with(|loading| loading.set(true));
+    render_list_end(views::ListEnd::Loading);
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        let query = list_route().list_query().unwrap_or_default();
+        let result = manager.client().quotes_page(&query, Some(&cursor)).await;
+        // The list changed while this page was loading; it belongs to the
+        // old one.
+        if LIST_GENERATION.with(Cell::get) != generation {
+            return;
+        }
+        LOADING_MORE.with(|loading| loading.set(false));
+
+        match result {
+            Ok(page) => {
+                let has_more = page.next_cursor.is_some();
+                append_quotes(&page.quotes);
+                NEXT_CURSOR.with(|cursor| *cursor.borrow_mut() = page.next_cursor);
+
+                render_list_end(if has_more { views::ListEnd::More } else { views::ListEnd::End });
+                // A short page may leave the end of the grid in view, and the
+                // observer only reports changes.
+                if has_more && list_end_in_reach() {
+                    load_more_quotes();
+                }
+            }
+            Err(e) => {
+                log!("Error fetching more quotes: {}", e);
+                render_list_end(views::ListEnd::Failed);
+            }
+        }
+    });
+}
+
+/// Adds cards after the ones on screen, leaving those untouched. Quotes
+/// already shown, e.g. added live since the first page, are skipped.
+fn append_quotes(quotes: &[Quote]) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(container) = document.get_element_by_id("quotes-container") else {
+        return;
+    };
+    let liked = liked_ids();
+    let preview_length = preview_length();
+    let mut html = String::new();
+    let mut appended = 0;
+    for quote in quotes {
+        let selector = format!(".quote-card[data-id=\"{}\"]", quote.id);
+        if container.query_selector(&selector).ok().flatten().is_none() {
+            html.push_str(&views::quote_card(quote, liked.contains(&quote.id), preview_length).into_string());
+            appended += 1;
+        }
+    }
+
+    container.insert_adjacent_html("beforeend", &html).unwrap();
+    adjust_quote_count(appended);
+}
+
+fn render_list_end(state: views::ListEnd) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(list_end) = document.get_element_by_id("list-end") {
+        list_end.set_inner_html(&views::list_end(state).into_string());
+    }
+}
+
+/// Whether `#list-end` is within `LIST_END_MARGIN_PX` of the viewport.
+fn list_end_in_reach() -> bool {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(list_end) = document.get_element_by_id("list-end") else {
+        return false;
+    };
+    let viewport = win.inner_height().ok().and_then(|height| height.as_f64()).unwrap_or(0.0);
+    list_end.get_bounding_client_rect().top() < viewport + LIST_END_MARGIN_PX
+}
+
+/// Loads the next page when `#list-end` comes within `LIST_END_MARGIN_PX`
+/// of the viewport. Its "Load more" button does the same by hand, and is
+/// all there is where `IntersectionObserver` is missing.
+fn setup_infinite_scroll() {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(list_end) = document.get_element_by_id("list-end") else {
+        return;
+    };
+    listen(Scope::Page, &list_end, "click", |event| {
+        let target = event.target().and_then(|target| target.dyn_into::<Element>().ok());
+        if target.is_some_and(|target| target.closest(".load-more-btn").ok().flatten().is_some()) {
+            load_more_quotes();
+        }
+    });
+
+    let on_intersect = IntersectionCallback::new(|entries: js_sys::Array| {
+        let intersecting = entries
+            .iter()
+            .filter_map(|entry| entry.dyn_into::<IntersectionObserverEntry>().ok())
+            .any(|entry| entry.is_intersecting());
+        if intersecting {
+            load_more_quotes();
+        }
+    });
+    let options = IntersectionObserverInit::new();
+    options.set_root_margin(&format!("0px 0px {}px 0px", LIST_END_MARGIN_PX));
+
+    match IntersectionObserver::new_with_options(on_intersect.as_ref().unchecked_ref(), &options) {
+        Ok(observer) => {
+            observer.observe(&list_end);
+            LIST_END_OBSERVER.with(|current| *current.borrow_mut() = Some((observer, on_intersect)));
+        }
+        Err(e) => {
+            log!("Infinite scroll unavailable: {:?}", e);
+        }
+    }
+}
+
+fn load_daily_quote() {
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        match manager.client().daily_quote().await {
+            Ok(quote) => {
+                display_daily_quote(quote);
+            }
+            Err(e) => {
+                log!("Error fetching quote of the day: {}", e);
+            }
+        }
+    });
+}
+
+fn display_daily_quote(quote: Quote) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(container) = document.get_element_by_id("daily-quote") else {
+        return;
+    };
+
+    if let Some(text) = document.get_element_by_id("daily-quote-text") {
+        text.set_text_content(Some(&format!("\"{}\"", quote.quote)));
+    }
+    if let Some(author) = document.get_element_by_id("daily-quote-author") {
+        let name = quote.author.unwrap_or_else(|| "Anonymous".to_string());
+        author.set_text_content(Some(&format!("— {}", name)));
+    }
+
+    let quote_id = quote.id;
+    listen(Scope::Page, &container, "click", move |_| navigate(Route::Quote(quote_id.clone())));
+    container.class_list().remove_1("hidden").unwrap();
+}
+
+fn get_random_quote() {
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        let session = RANDOM_SESSION.with(|session| session.clone());
+        match manager.client().random_quote(Some(&session)).await {
+            Ok(quote) => {
+                show_quote_modal(&quote);
+                navigate(Route::Quote(quote.id.clone()));
+            }
+            Err(e) => {
+                log!("Error fetching random quote: {}", e);
+                show_error_modal("No quotes available. Add some quotes first!");
+            }
+        }
+    });
+}
+
+fn show_error_modal(message: &str) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(modal) = document.get_element_by_id("quote-modal") {
+        if let Some(modal_content) = document.get_element_by_id("modal-content") {
+            listeners::clear(Scope::Modal);
+            modal_content.set_inner_html(&views::error_modal(message).into_string());
+
+            let modal: HtmlElement = modal.dyn_into().unwrap();
+            modal.class_list().remove_1("hidden").unwrap();
+
+            for id in ["modal-close", "close-error"] {
+                if let Some(button) = document.get_element_by_id(id) {
+                    listen(Scope::Modal, &button, "click", |_| close_modal());
+                }
             }
         }
     }
@@ -514,117 +1229,398 @@ fn display_quotes(quotes: Vec<Quote>) {
     let document = win.document().unwrap();
 
     if let Some(quotes_container) = document.get_element_by_id("quotes-container") {
-        let mut html = String::new();
-
-        for quote in quotes {
-            let author = quote.author.unwrap_or_else(|| "Anonymous".to_string());
-            let preview = if quote.quote.len() > 150 {
-                format!("{}...", &quote.quote[..150])
-            } else {
-                quote.quote.clone()
+        let liked = liked_ids();
+        let preview_length = preview_length();
+        let html = if quotes.is_empty() {
+            let filtered = list_route().list_query().is_some_and(|query| query.is_filtered());
+            views::empty_quotes(filtered).into_string()
+        } else {
+            quotes
+                .iter()
+                .map(|quote| views::quote_card(quote, liked.contains(&quote.id), preview_length).into_string())
+                .collect()
+        };
+
+        quotes_container.set_inner_html(&html);
+    }
+}
+
+fn preview_length() -> usize {
+    window()
+        .and_then(|win| win.document())
+        .and_then(|document| document.get_element_by_id("quotes-container"))
+        .and_then(|container| container.get_attribute("data-preview-length"))
+        .and_then(|length| length.trim().parse().ok())
+        .filter(|&length| length > 0)
+        .unwrap_or(DEFAULT_PREVIEW_LENGTH)
+}
+
+/// The start of `text`, at most `max_graphemes` long, or `None` if it all
+/// fits. Counts graphemes so emoji and combining marks are never split, and
+/// ends on a word boundary unless that would drop most of the preview (as
+/// with one very long word).
+fn truncate_preview(text: &str, max_graphemes: usize) -> Option<&str> {
+    let cut = text.grapheme_indices(true).nth(max_graphemes).map(|(index, _)| index)?;
+    let word_end = text
+        .split_word_bound_indices()
+        .map(|(index, _)| index)
+        .take_while(|&index| index <= cut)
+        .last()
+        .unwrap_or(0);
+    let end = if word_end >= cut / 2 { word_end } else { cut };
+    Some(text[..end].trim_end())
+}
+
+fn quote_card(quote: &Quote) -> String {
+    views::quote_card(quote, liked_ids().contains(&quote.id), preview_length()).into_string()
+}
+
+/// Listens for quotes created, edited or deleted elsewhere and patches the
+/// grid in place. `EventSource` reconnects on its own and sends
+/// `Last-Event-ID`, so nothing is missed across short drops.
+fn subscribe_to_quote_stream() {
+    let manager = quote_manager();
+    let source = match EventSource::new(&manager.stream_url()) {
+        Ok(source) => source,
+        Err(e) => {
+            log!("Live updates unavailable: {:?}", e);
+            return;
+        }
+    };
+
+    for name in ["created", "updated", "deleted"] {
+        listen(Scope::Page, &source, name, |event| {
+            let Some(data) = event.unchecked_into::<MessageEvent>().data().as_string() else {
+                return;
             };
+            match serde_json::from_str::<QuoteEvent>(&data) {
+                Ok(quote_event) => apply_quote_event(quote_event),
+                Err(e) => {
+                    log!("Unreadable quote event: {}", e);
+                }
+            }
+        });
+    }
 
-            html.push_str(
-                &format!(
-                    r#"
-                <div class="glass-morphism rounded-2xl p-6 cyber-border card-hover cursor-pointer quote-card group" data-id="{}">
-                    <div class="space-y-4">
-                        <div class="flex items-start justify-between">
-                            <div class="flex-1">
-                                <blockquote class="text-gray-100 font-medium leading-relaxed text-lg group-hover:text-white transition-colors">
-                                    "{}"
-                                </blockquote>
-                            </div>
-                            <div class="ml-4 opacity-50 group-hover:opacity-100 transition-opacity">
-                                <svg class="w-5 h-5 text-neon-cyan" fill="currentColor" viewBox="0 0 20 20">
-                                    <path fill-rule="evenodd" d="M18 10a8 8 0 11-16 0 8 8 0 0116 0zm-6-3a2 2 0 11-4 0 2 2 0 014 0zm-2 4a5 5 0 00-4.546 2.916A5.986 5.986 0 0010 16a5.986 5.986 0 004.546-2.084A5 5 0 0010 11z" clip-rule="evenodd" />
-                                </svg>
-                            </div>
-                        </div>
-                        
-                        <div class="flex items-center justify-between pt-4 border-t border-dark-700 group-hover:border-neon-cyan/30 transition-colors">
-                            <cite class="text-neon-cyan font-bold group-hover:text-white transition-colors">
-                                — {}
-                            </cite>
-                            <div class="text-xs text-gray-500 font-mono group-hover:text-gray-400 transition-colors">
-                                {}
-                            </div>
-                        </div>
-                        
-                        <div class="opacity-0 group-hover:opacity-100 transition-all duration-300 text-center">
-                            <div class="text-xs text-neon-purple font-mono">
-                                Click to expand
-                            </div>
-                        </div>
-                    </div>
-                </div>
-                "#,
-                    quote.id,
-                    preview,
-                    author,
-                    quote.created_at.split('T').next().unwrap_or("")
-                )
-            );
+    // Sent when the server could not replay everything we missed.
+    listen(Scope::Page, &source, "resync", |_| load_quotes());
+}
+
+fn apply_quote_event(event: QuoteEvent) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(container) = document.get_element_by_id("quotes-container") else {
+        return;
+    };
+    let find_card = |id: &str| document.query_selector(&format!(".quote-card[data-id=\"{}\"]", id)).ok().flatten();
+
+    match event {
+        QuoteEvent::Created { quote } => {
+            // Translations are not listed, and our own submissions may
+            // already be on screen after `load_quotes`. Whether a quote
+            // matches a filtered list is the server's call, and only the
+            // newest-first order puts it at the top, so other lists wait for
+            // a reload.
+            let newest_first = list_route()
+                .list_query()
+                .is_some_and(|query| !query.is_filtered() && query.sort == QuoteSort::Newest);
+            if quote.translation_of.is_some() || !newest_first || find_card(&quote.id).is_some() {
+                return;
+            }
+            if let Ok(Some(placeholder)) = container.query_selector(".col-span-full") {
+                placeholder.remove();
+            }
+            container
+                .insert_adjacent_html("afterbegin", &quote_card(&quote))
+                .unwrap();
+            adjust_quote_count(1);
+        }
+        QuoteEvent::Updated { quote } => {
+            if let Some(card) = find_card(&quote.id) {
+                card.set_outer_html(&quote_card(&quote));
+            }
         }
+        QuoteEvent::Deleted { id } => {
+            if let Some(card) = find_card(&id) {
+                card.remove();
+                adjust_quote_count(-1);
+            }
+        }
+    }
+}
+
+fn adjust_quote_count(delta: i64) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
 
-        if html.is_empty() {
-            html =
-                r#"
-                <div class="col-span-full">
-                    <div class="glass-morphism rounded-2xl p-12 text-center cyber-border">
-                        <div class="space-y-4">
-                            <div class="text-6xl">📝</div>
-                            <div class="text-white text-xl font-bold">No quotes found</div>
-                            <p class="text-gray-400">Be the first to share your wisdom with the world!</p>
-                            <div class="text-gray-500 font-mono text-sm">
-                                { quotes.is_empty() }
-                            </div>
-                        </div>
-                    </div>
-                </div>
-            "#.to_string();
+    if let Some(count_element) = document.get_element_by_id("quote-count") {
+        if let Some(count) = count_element.text_content().and_then(|text| text.trim().parse::<i64>().ok()) {
+            count_element.set_text_content(Some(&(count + delta).max(0).to_string()));
         }
+    }
+}
 
-        quotes_container.set_inner_html(&html);
-        setup_quote_card_handlers();
+fn toggle_like(quote_id: &str) {
+    let quote_id_owned = quote_id.to_string();
+    let currently_liked = liked_ids().contains(&quote_id_owned);
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        let result = if currently_liked {
+            manager.client().unlike(&quote_id_owned).await
+        } else {
+            manager.client().like(&quote_id_owned).await
+        };
+
+        match result {
+            Ok(status) => {
+                remember_like(&status.id, status.liked);
+                render_like_state(&status);
+            }
+            Err(e) => {
+                log!("Error updating like: {}", e);
+            }
+        }
+    });
+}
+
+/// Updates every heart for the quote, so a card and an open modal stay in sync.
+fn render_like_state(status: &LikeStatus) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let selector = format!(".like-btn[data-id=\"{}\"]", status.id);
+    if let Ok(buttons) = document.query_selector_all(&selector) {
+        for i in 0..buttons.length() {
+            if let Some(button) = buttons.get(i).and_then(|node| node.dyn_into::<web_sys::Element>().ok()) {
+                let classes = button.class_list();
+                let _ = classes.toggle_with_force("text-neon-pink", status.liked);
+                let _ = classes.toggle_with_force("text-gray-500", !status.liked);
+                let _ = classes.toggle_with_force("hover:text-neon-pink", !status.liked);
+            }
+        }
     }
+    render_like_count(&status.id, status.like_count);
 }
 
-fn setup_quote_card_handlers() {
+fn render_like_count(quote_id: &str, like_count: u32) {
     let win = window().unwrap();
     let document = win.document().unwrap();
 
-    if let Ok(quote_cards) = document.query_selector_all(".quote-card") {
-        for i in 0..quote_cards.length() {
-            if let Some(card) = quote_cards.get(i) {
-                let card: HtmlElement = card.dyn_into().unwrap();
-                if let Some(quote_id) = card.get_attribute("data-id") {
-                    let quote_id_clone = quote_id.clone();
-                    let closure = Closure::wrap(
-                        Box::new(move || {
-                            show_quote_detail(&quote_id_clone);
-                        }) as Box<dyn Fn()>
-                    );
+    let selector = format!(".like-btn[data-id=\"{}\"] .like-count", quote_id);
+    if let Ok(counts) = document.query_selector_all(&selector) {
+        for i in 0..counts.length() {
+            if let Some(count) = counts.get(i) {
+                count.set_text_content(Some(&like_count.to_string()));
+            }
+        }
+    }
+}
 
-                    card.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                    closure.forget();
-                }
+/// Opens the live connection for like counts, presence and (for moderators)
+/// moderation notices, reconnecting whenever it drops.
+fn connect_live_socket() {
+    let manager = quote_manager();
+    let socket = match WebSocket::new(&manager.live_url()) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log!("Live connection unavailable: {:?}", e);
+            schedule_live_reconnect();
+            return;
+        }
+    };
+
+    // The previous socket, if any, has closed; its handlers go with it.
+    listeners::clear(Scope::LiveSocket);
+
+    listen(Scope::LiveSocket, &socket, "open", |_| {
+        if let Some(token) = local_storage().and_then(|storage| storage.get_item(ADMIN_TOKEN_KEY).ok().flatten()) {
+            send_live(&ClientMessage::Authenticate { token });
+        }
+        if let Some(quote_id) = VIEWING.with(|viewing| viewing.borrow().clone()) {
+            send_live(&ClientMessage::View { quote_id });
+        }
+    });
+
+    listen(Scope::LiveSocket, &socket, "message", |event| {
+        let Some(data) = event.unchecked_into::<MessageEvent>().data().as_string() else {
+            return;
+        };
+        match serde_json::from_str::<ServerMessage>(&data) {
+            Ok(message) => handle_live_message(message),
+            Err(e) => {
+                log!("Unreadable live message: {}", e);
+            }
+        }
+    });
+
+    listen(Scope::LiveSocket, &socket, "close", |_| {
+        LIVE_SOCKET.with(|live| live.borrow_mut().take());
+        schedule_live_reconnect();
+    });
+
+    LIVE_SOCKET.with(|live| *live.borrow_mut() = Some(socket));
+}
+
+fn schedule_live_reconnect() {
+    let reconnect = Closure::once_into_js(connect_live_socket);
+    let _ = window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(reconnect.unchecked_ref(), LIVE_RECONNECT_MS);
+}
+
+/// Sends over the live connection if it is open; messages sent while it is
+/// down are dropped, and `on_open` re-announces what matters.
+fn send_live(message: &ClientMessage) {
+    let Ok(json) = serde_json::to_string(message) else {
+        return;
+    };
+    LIVE_SOCKET.with(|live| {
+        if let Some(socket) = live.borrow().as_ref() {
+            if socket.ready_state() == WebSocket::OPEN {
+                let _ = socket.send_with_str(&json);
             }
         }
+    });
+}
+
+fn handle_live_message(message: ServerMessage) {
+    match message {
+        ServerMessage::LikeCount { quote_id, like_count } => render_like_count(&quote_id, like_count),
+        ServerMessage::Presence { quote_id, viewers } => render_viewers(&quote_id, viewers),
+        ServerMessage::Moderation { action, quote_id, detail } => {
+            show_moderation_notice(action, quote_id.as_deref(), detail.as_deref());
+        }
+        ServerMessage::Authenticated { admin } => {
+            log!("Live connection authenticated (admin: {})", admin);
+        }
+        ServerMessage::Pong => {}
+        ServerMessage::Error { message } => {
+            log!("Live connection error: {}", message);
+        }
+    }
+}
+
+fn render_viewers(quote_id: &str, viewers: u32) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(element) = document.get_element_by_id("quote-viewers") else {
+        return;
+    };
+    if element.get_attribute("data-id").as_deref() != Some(quote_id) {
+        return;
+    }
+
+    // Only worth showing when someone besides us is here.
+    if viewers > 1 {
+        element.set_text_content(Some(&format!("{} people viewing now", viewers)));
+        element.class_list().remove_1("hidden").unwrap();
+    } else {
+        element.class_list().add_1("hidden").unwrap();
+    }
+}
+
+fn show_moderation_notice(action: ModerationAction, quote_id: Option<&str>, detail: Option<&str>) {
+    let verb = match action {
+        ModerationAction::Rejected => "Submission rejected",
+        ModerationAction::Edited => "Quote edited",
+        ModerationAction::Reverted => "Quote reverted",
+        ModerationAction::Deleted => "Quote deleted",
+        ModerationAction::Restored => "Quote restored",
+    };
+    let mut text = verb.to_string();
+    if let Some(quote_id) = quote_id {
+        text.push_str(&format!(" · {}", quote_id));
+    }
+    if let Some(detail) = detail {
+        text.push_str(&format!(" · {}", detail));
     }
+    show_toast(&text);
+}
+
+fn show_toast(text: &str) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+    let Some(body) = document.body() else {
+        return;
+    };
+
+    let toast = document.create_element("div").unwrap();
+    toast.set_class_name(
+        "fixed bottom-6 right-6 z-50 max-w-sm glass-morphism cyber-border rounded-xl px-4 py-3 text-sm font-mono text-gray-200"
+    );
+    toast.set_text_content(Some(text));
+    body.append_child(&toast).unwrap();
+
+    let remove = Closure::once_into_js(move || toast.remove());
+    let _ = win.set_timeout_with_callback_and_timeout_and_arguments_0(remove.unchecked_ref(), 6000);
+}
+
+/// One click listener for every card in the grid, present and future, so
+/// re-rendering the grid never needs new handlers.
+fn setup_quote_grid() {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(container) = document.get_element_by_id("quotes-container") else {
+        return;
+    };
+    listen(Scope::Page, &container, "click", |event| {
+        let Some(target) = event.target().and_then(|target| target.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        let closest = |selector: &str| target.closest(selector).ok().flatten();
+
+        // Buttons first, so clicking one does not also open its card.
+        if let Some(button) = closest(".like-btn") {
+            if let Some(quote_id) = button.get_attribute("data-id") {
+                toggle_like(&quote_id);
+            }
+        } else if let Some(button) = closest(".expand-btn") {
+            toggle_expanded(&button);
+        } else if let Some(card) = closest(".quote-card") {
+            if let Some(quote_id) = card.get_attribute("data-id") {
+                navigate(Route::Quote(quote_id));
+            }
+        }
+    });
+}
+
+/// "Show more" swaps a card's preview for the full quote in place instead
+/// of opening the modal.
+fn toggle_expanded(button: &Element) {
+    let Ok(Some(card)) = button.closest(".quote-card") else {
+        return;
+    };
+    let (Ok(Some(preview)), Ok(Some(full))) = (
+        card.query_selector(".quote-preview"),
+        card.query_selector(".quote-full"),
+    ) else {
+        return;
+    };
+
+    // `toggle` reports whether the class is now present.
+    let expanded = !full.class_list().toggle("hidden").unwrap_or(true);
+    let _ = preview.class_list().toggle("hidden");
+    button.set_text_content(Some(if expanded { "Show less" } else { "Show more" }));
+    let _ = button.set_attribute("aria-expanded", if expanded { "true" } else { "false" });
 }
 
 fn show_quote_detail(quote_id: &str) {
     let quote_id_owned = quote_id.to_string();
     wasm_bindgen_futures::spawn_local(async move {
-        let manager = QuoteManager::new();
-        match manager.get_quote_by_id(&quote_id_owned).await {
-            Ok(quote_js) => {
-                let quote: Quote = serde_wasm_bindgen::from_value(quote_js).unwrap();
+        let manager = quote_manager();
+        match manager.client().quote(&quote_id_owned).await {
+            Ok(quote) => {
                 show_quote_modal(&quote);
             }
+            Err(e) if e.is_not_found() => {
+                show_error_modal("This quote no longer exists.");
+            }
             Err(e) => {
-                log!("Error fetching quote: {:?}", e);
+                log!("Error fetching quote: {}", e);
                 show_error_modal("Could not load quote details.");
             }
         }
@@ -637,89 +1633,466 @@ fn show_quote_modal(quote: &Quote) {
 
     if let Some(modal) = document.get_element_by_id("quote-modal") {
         if let Some(modal_content) = document.get_element_by_id("modal-content") {
-            let author = quote.author.clone().unwrap_or_else(|| "Anonymous".to_string());
-            
-            modal_content.set_inner_html(
-                &format!(
-                    r#"
-                <div class="glass-morphism rounded-3xl p-8 cyber-border max-w-4xl w-full mx-auto transform transition-all">
-                    <div class="mb-8">
-                        <div>
-                            <h3 class="text-3xl font-bold text-white mb-2">Quote Details</h3>
-                            <p class="text-gray-400 font-mono text-sm">{{ quote.render() }}</p>
-                        </div>
-                    </div>
-                    
-                    <div class="text-center space-y-8">
-                        <div class="relative">
-                            <div class="absolute -top-4 -left-4 text-6xl text-neon-cyan opacity-20">"</div>
-                            <blockquote class="text-2xl md:text-3xl font-medium text-white leading-relaxed px-8">
-                                {}
-                            </blockquote>
-                            <div class="absolute -bottom-4 -right-4 text-6xl text-neon-cyan opacity-20">"</div>
-                        </div>
-                        
-                        <div class="space-y-4">
-                            <cite class="text-xl text-neon-cyan font-bold">— {}</cite>
-                            <div class="inline-flex items-center gap-2 text-gray-400 font-mono text-sm">
-                                <svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20">
-                                    <path fill-rule="evenodd" d="M6 2a1 1 0 00-1 1v1H4a2 2 0 00-2 2v10a2 2 0 002 2h12a2 2 0 002-2V6a2 2 0 00-2-2h-1V3a1 1 0 10-2 0v1H7V3a1 1 0 00-1-1zm0 5a1 1 0 000 2h8a1 1 0 100-2H6z" clip-rule="evenodd" />
-                                </svg>
-                                Added on {}
-                            </div>
-                        </div>
-                        
-                        <div class="flex flex-col sm:flex-row gap-4 justify-center pt-4">
-                            <button id="another-random" class="bg-gradient-to-r from-neon-purple to-neon-cyan text-dark-950 font-bold py-3 px-8 rounded-xl hover:from-neon-cyan hover:to-neon-purple transition-all transform hover:scale-105 shadow-lg hover:shadow-neon-cyan/25">
-                                <span class="flex items-center gap-2">
-                                    <svg class="w-5 h-5" fill="currentColor" viewBox="0 0 20 20">
-                                        <path fill-rule="evenodd" d="M4 2a1 1 0 011 1v2.101a7.002 7.002 0 0111.601 2.566 1 1 0 11-1.885.666A5.002 5.002 0 005.999 7H9a1 1 0 010 2H4a1 1 0 01-1-1V3a1 1 0 011-1zm.008 9.057a1 1 0 011.276.61A5.002 5.002 0 0014.001 13H11a1 1 0 110-2h5a1 1 0 011 1v5a1 1 0 11-2 0v-2.101a7.002 7.002 0 01-11.601-2.566 1 1 0 01.61-1.276z" clip-rule="evenodd" />
-                                    </svg>
-                                    Another Quote
-                                </span>
-                            </button>
-                            
-                            <button id="close-modal-btn" class="bg-dark-800 border border-dark-600 text-gray-300 font-bold py-3 px-8 rounded-xl hover:bg-dark-700 hover:border-dark-500 transition-all">
-                                <span class="flex items-center gap-2">
-                                    Close
-                                </span>
-                            </button>
-                        </div>
-                    </div>
-                </div>
-                "#,
-                    quote.quote,
-                    author,
-                    quote.created_at.split('T').next().unwrap_or("")
-                )
-            );
+            let liked = liked_ids().contains(&quote.id);
+            let share_url = quote_manager().share_url(&quote.id);
+            listeners::clear(Scope::Modal);
+            let list = list_route().list_query().unwrap_or_default();
+            modal_content.set_inner_html(&views::quote_modal(quote, liked, &share_url, &list).into_string());
 
             let modal: HtmlElement = modal.dyn_into().unwrap();
             modal.class_list().remove_1("hidden").unwrap();
+            render_quote_source(quote);
+            VIEWING.with(|viewing| *viewing.borrow_mut() = Some(quote.id.clone()));
+            send_live(&ClientMessage::View { quote_id: quote.id.clone() });
+            load_translations(quote);
+
+            if let Ok(Some(like_btn)) = modal_content.query_selector(".like-btn") {
+                let quote_id = quote.id.clone();
+                listen(Scope::Modal, &like_btn, "click", move |_| toggle_like(&quote_id));
+            }
 
             if let Some(close_btn) = document.get_element_by_id("close-modal-btn") {
-                let close_btn: HtmlElement = close_btn.dyn_into().unwrap();
-                let closure = Closure::wrap(
-                    Box::new(move || {
-                        close_modal();
-                    }) as Box<dyn Fn()>
-                );
-                
-                close_btn.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                closure.forget();
+                listen(Scope::Modal, &close_btn, "click", |_| close_modal());
+            }
+
+            if let Some(save_btn) = document.get_element_by_id("save-to-collection") {
+                let quote_id = quote.id.clone();
+                listen(Scope::Modal, &save_btn, "click", move |_| open_collection_picker(&quote_id));
+            }
+
+            if let Some(form) = document.get_element_by_id("new-collection-form") {
+                let quote_id = quote.id.clone();
+                listen(Scope::Modal, &form, "submit", move |event| {
+                    event.prevent_default();
+                    create_collection_with_quote(&quote_id);
+                });
             }
 
             if let Some(another_btn) = document.get_element_by_id("another-random") {
-                let another_btn: HtmlElement = another_btn.dyn_into().unwrap();
-                let closure = Closure::wrap(
-                    Box::new(move || {
-                        get_random_quote();
-                    }) as Box<dyn Fn()>
+                listen(Scope::Modal, &another_btn, "click", |_| get_random_quote());
+            }
+        }
+    }
+}
+
+/// Fills the source panel of the detail modal. Built from DOM nodes because
+/// the fields are free text.
+fn render_quote_source(quote: &Quote) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(source) = quote.source.as_ref() else {
+        return;
+    };
+    let (Some(panel), Some(details)) = (
+        document.get_element_by_id("quote-source"),
+        document.get_element_by_id("source-details"),
+    ) else {
+        return;
+    };
+
+    let mut lines: Vec<(&str, String)> = Vec::new();
+    if let Some(title) = &source.title {
+        lines.push(("Work", title.clone()));
+    }
+    if let Some(year) = source.year {
+        lines.push(("Year", if year < 0 { format!("{} BCE", -year) } else { year.to_string() }));
+    }
+    if let Some(page) = &source.page {
+        lines.push(("Page", page.clone()));
+    }
+
+    for (label, value) in lines {
+        let line = document.create_element("div").unwrap();
+        let label_el = document.create_element("span").unwrap();
+        label_el.set_class_name("text-gray-500 font-mono mr-2");
+        label_el.set_text_content(Some(label));
+        line.append_child(&label_el).unwrap();
+        let value_el = document.create_element("span").unwrap();
+        value_el.set_text_content(Some(&value));
+        line.append_child(&value_el).unwrap();
+        details.append_child(&line).unwrap();
+    }
+
+    if let Some(url) = source.url.as_ref().filter(|url| url.starts_with("http://") || url.starts_with("https://")) {
+        let link = document.create_element("a").unwrap();
+        link.set_class_name("text-neon-cyan hover:underline break-all");
+        link.set_attribute("href", url).unwrap();
+        link.set_attribute("target", "_blank").unwrap();
+        link.set_attribute("rel", "noopener noreferrer").unwrap();
+        link.set_text_content(Some(url));
+        details.append_child(&link).unwrap();
+    }
+
+    if source.verified {
+        if let Some(badge) = document.get_element_by_id("source-verified") {
+            badge.class_list().remove_1("hidden").unwrap();
+        }
+    }
+    panel.class_list().remove_1("hidden").unwrap();
+
+    if source.title.is_none() {
+        return;
+    }
+
+    load_citation(&quote.id, "apa");
+
+    if let Some(select) = document.get_element_by_id("citation-style") {
+        let select: HtmlSelectElement = select.dyn_into().unwrap();
+        let quote_id = quote.id.clone();
+        let select_for_closure = select.clone();
+        listen(Scope::Modal, &select, "change", move |_| load_citation(&quote_id, &select_for_closure.value()));
+    }
+}
+
+/// Shows the quote's text and language in the detail modal.
+fn show_quote_version(quote: &Quote) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(text) = document.get_element_by_id("modal-quote-text") {
+        text.set_text_content(Some(&quote.quote));
+        match &quote.language {
+            Some(language) => text.set_attribute("lang", language).unwrap(),
+            None => text.remove_attribute("lang").unwrap(),
+        }
+    }
+    if let Some(author) = document.get_element_by_id("modal-quote-author") {
+        let name = quote.author.clone().unwrap_or_else(|| "Anonymous".to_string());
+        author.set_text_content(Some(&format!("— {}", name)));
+    }
+}
+
+fn load_translations(quote: &Quote) {
+    let quote = quote.clone();
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        match manager.client().translations(&quote.id).await {
+            Ok(translations) => {
+                if !translations.is_empty() {
+                    let mut versions = vec![quote];
+                    versions.extend(translations);
+                    render_translation_toggle(versions);
+                }
+            }
+            Err(e) => {
+                log!("Error fetching translations: {}", e);
+            }
+        }
+    });
+}
+
+/// One button per language version; the first entry is the quote the modal
+/// was opened with.
+fn render_translation_toggle(versions: Vec<Quote>) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(toggle) = document.get_element_by_id("translation-toggle") else {
+        return;
+    };
+    listeners::clear(Scope::Translations);
+    toggle.set_inner_html("");
+
+    for (index, version) in versions.iter().enumerate() {
+        let button = document.create_element("button").unwrap();
+        button.set_class_name(
+            "translation-option px-3 py-1 rounded-full border font-mono text-xs uppercase tracking-wider transition-colors"
+        );
+        let label = version.language.clone().unwrap_or_else(|| "?".to_string());
+        let label = if version.translation_of.is_none() { format!("{} · original", label) } else { label };
+        button.set_text_content(Some(&label));
+        button.class_list().add_1(if index == 0 { "border-neon-cyan" } else { "border-dark-600" }).unwrap();
+
+        let version = version.clone();
+        listen(Scope::Translations, &button, "click", move |event| {
+            show_quote_version(&version);
+
+            let document = window().unwrap().document().unwrap();
+            if let Ok(options) = document.query_selector_all(".translation-option") {
+                for i in 0..options.length() {
+                    if let Some(option) = options.get(i).and_then(|node| node.dyn_into::<HtmlElement>().ok()) {
+                        option.class_list().remove_1("border-neon-cyan").unwrap();
+                        option.class_list().add_1("border-dark-600").unwrap();
+                    }
+                }
+            }
+            if let Some(target) = event.current_target().and_then(|target| target.dyn_into::<HtmlElement>().ok()) {
+                target.class_list().remove_1("border-dark-600").unwrap();
+                target.class_list().add_1("border-neon-cyan").unwrap();
+            }
+        });
+        toggle.append_child(&button).unwrap();
+    }
+
+    toggle.class_list().remove_1("hidden").unwrap();
+}
+
+fn load_citation(quote_id: &str, style: &str) {
+    let quote_id = quote_id.to_string();
+    let style = style.to_string();
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        let text = match manager.client().citation(&quote_id, &style).await {
+            Ok(citation) => citation.citation,
+            Err(e) => {
+                log!("Error fetching citation: {}", e);
+                "Citation unavailable.".to_string()
+            }
+        };
+
+        let document = window().unwrap().document().unwrap();
+        if let Some(citation) = document.get_element_by_id("citation-text") {
+            citation.set_text_content(Some(&text));
+        }
+    });
+}
+
+fn set_collection_status(message: &str) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    if let Some(status) = document.get_element_by_id("collection-status") {
+        status.set_text_content(Some(message));
+    }
+}
+
+fn open_collection_picker(quote_id: &str) {
+    let quote_id_owned = quote_id.to_string();
+
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+    if let Some(picker) = document.get_element_by_id("collection-picker") {
+        picker.class_list().remove_1("hidden").unwrap();
+    }
+    set_collection_status("Loading your collections...");
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        match manager.client().collections(true).await {
+            Ok(collections) => {
+                render_collection_options(&collections, &quote_id_owned);
+                set_collection_status(
+                    if collections.is_empty() { "No collections yet. Create one below." } else { "" }
                 );
-                
-                another_btn.set_onclick(Some(closure.as_ref().unchecked_ref()));
-                closure.forget();
             }
+            Err(e) => {
+                log!("Error fetching collections: {}", e);
+                set_collection_status("Could not load collections.");
+            }
+        }
+    });
+}
+
+fn render_collection_options(collections: &[Collection], quote_id: &str) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let Some(options) = document.get_element_by_id("collection-options") else {
+        return;
+    };
+    listeners::clear(Scope::CollectionOptions);
+    options.set_inner_html("");
+
+    for collection in collections {
+        let button = document.create_element("button").unwrap();
+        button.set_class_name(
+            "collection-option px-4 py-2 rounded-lg border border-dark-600 text-gray-300 hover:border-neon-purple hover:text-neon-purple transition-colors text-sm"
+        );
+        button.set_text_content(Some(&format!("{} ({})", collection.name, collection.quote_count)));
+
+        let slug = collection.slug.clone();
+        let name = collection.name.clone();
+        let quote_id = quote_id.to_string();
+        listen(Scope::CollectionOptions, &button, "click", move |_| save_to_collection(&slug, &name, &quote_id));
+        options.append_child(&button).unwrap();
+    }
+}
+
+fn save_to_collection(slug: &str, name: &str, quote_id: &str) {
+    let slug = slug.to_string();
+    let name = name.to_string();
+    let quote_id = quote_id.to_string();
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        match manager.client().add_to_collection(&slug, &quote_id).await {
+            Ok(_) => set_collection_status(&format!("Saved to {}", name)),
+            Err(e) => {
+                log!("Error saving to collection: {}", e);
+                set_collection_status("Could not save to collection.");
+            }
+        }
+    });
+}
+
+fn create_collection_with_quote(quote_id: &str) {
+    let win = window().unwrap();
+    let document = win.document().unwrap();
+
+    let name = document
+        .get_element_by_id("new-collection-name")
+        .and_then(|input| input.dyn_into::<HtmlInputElement>().ok())
+        .map(|input| input.value())
+        .unwrap_or_default();
+    let is_public = document
+        .get_element_by_id("new-collection-public")
+        .and_then(|input| input.dyn_into::<HtmlInputElement>().ok())
+        .is_some_and(|input| input.checked());
+
+    if name.trim().is_empty() {
+        return;
+    }
+
+    let quote_id = quote_id.to_string();
+    wasm_bindgen_futures::spawn_local(async move {
+        let manager = quote_manager();
+        match manager.client().create_collection(name.trim(), is_public).await {
+            Ok(CollectionDetail { collection, .. }) => {
+                if let Err(e) = manager.client().add_to_collection(&collection.slug, &quote_id).await {
+                    log!("Error saving to collection: {}", e);
+                    set_collection_status("Collection created, but the quote could not be saved.");
+                    return;
+                }
+
+                if let Ok(collections) = manager.client().collections(true).await {
+                    render_collection_options(&collections, &quote_id);
+                }
+                set_collection_status(&format!("Saved to {}", collection.name));
+            }
+            Err(e) => {
+                log!("Error creating collection: {}", e);
+                set_collection_status("Could not create collection.");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> QuoteListQuery {
+        QuoteListQuery::default()
+    }
+
+    #[test]
+    fn short_text_is_not_truncated() {
+        assert_eq!(truncate_preview("", 5), None);
+        assert_eq!(truncate_preview("héllo", 5), None);
+        // Combining accents belong to the grapheme before them.
+        assert_eq!(truncate_preview("e\u{301}e\u{301}e\u{301}", 3), None);
+    }
+
+    #[test]
+    fn previews_end_on_a_word_boundary() {
+        assert_eq!(truncate_preview("ééééé ééééé", 8), Some("ééééé"));
+        assert_eq!(truncate_preview("Привет, мир! Как дела?", 15), Some("Привет, мир!"));
+    }
+
+    #[test]
+    fn previews_cut_long_words_between_graphemes() {
+        assert_eq!(truncate_preview("ñññññññññññ", 4), Some("ññññ"));
+        assert_eq!(truncate_preview("e\u{301}e\u{301}e\u{301}e\u{301}", 2), Some("e\u{301}e\u{301}"));
+        let family = "👨\u{200d}👩\u{200d}👧";
+        assert_eq!(truncate_preview(&family.repeat(4), 2), Some(family.repeat(2).as_str()));
+    }
+
+    #[test]
+    fn components_are_encoded_like_encode_uri_component() {
+        assert_eq!(encode_component("a b&c/d?e=é#"), "a%20b%26c%2Fd%3Fe%3D%C3%A9%23");
+        assert_eq!(encode_component("AZaz09-_.!~*'()"), "AZaz09-_.!~*'()");
+        assert_eq!(encode_component("+"), "%2B");
+    }
+
+    #[test]
+    fn components_decode_back() {
+        for text in ["café & crème", "100% 🦀", "a+b", "日本語/タグ?"] {
+            assert_eq!(decode_component(&encode_component(text)), text);
+        }
+        assert_eq!(decode_component("hello+world"), "hello world");
+        assert_eq!(decode_component("%e2%9c%93"), "✓");
+    }
+
+    #[test]
+    fn malformed_components_are_kept() {
+        assert_eq!(decode_component("100%"), "100%");
+        assert_eq!(decode_component("%zz%2"), "%zz%2");
+        assert_eq!(decode_component("%+1"), "% 1");
+        // Not UTF-8 once decoded.
+        assert_eq!(decode_component("%FF%FE"), "%FF%FE");
+    }
+
+    #[test]
+    fn quote_routes_round_trip() {
+        let route = Route::Quote("a1b2/c3 d4".to_string());
+        assert_eq!(route.fragment(), "#/quotes/a1b2%2Fc3%20d4");
+        assert_eq!(Route::parse(&route.fragment()), route);
+        assert_eq!(route.list_query(), None);
+    }
+
+    #[test]
+    fn empty_fragments_are_the_home_list() {
+        for fragment in ["", "#", "#/", "#/unknown/path", "#/?sort=sideways&limit=7"] {
+            assert_eq!(Route::parse(fragment), Route::home(), "{:?}", fragment);
+        }
+        assert_eq!(Route::home().fragment(), "#/");
+    }
+
+    #[test]
+    fn a_single_author_or_tag_filter_is_a_path() {
+        let by_author = Route::Quotes(QuoteListQuery { author: Some("Ada Lovelace".to_string()), ..list() });
+        assert_eq!(by_author.fragment(), "#/authors/Ada%20Lovelace");
+        assert_eq!(Route::parse(&by_author.fragment()), by_author);
+
+        let by_tag = Route::Quotes(QuoteListQuery { tag: Some("c++".to_string()), ..list() });
+        assert_eq!(by_tag.fragment(), "#/tags/c%2B%2B");
+        assert_eq!(Route::parse(&by_tag.fragment()), by_tag);
+    }
+
+    #[test]
+    fn filtered_paths_keep_the_order_and_page_size() {
+        let sorted = QuoteListQuery { sort: QuoteSort::Popular, limit: Some(20), search: Some("wit".to_string()), ..list() };
+        let by_author = Route::Quotes(sorted.for_author("Ada Lovelace"));
+        assert_eq!(by_author.fragment(), "#/authors/Ada%20Lovelace?sort=popular&limit=20");
+        assert_eq!(Route::parse(&by_author.fragment()), by_author);
+
+        let by_tag = Route::Quotes(sorted.for_tag("c++"));
+        assert_eq!(by_tag.fragment(), "#/tags/c%2B%2B?sort=popular&limit=20");
+        assert_eq!(Route::parse(&by_tag.fragment()), by_tag);
+    }
+
+    #[test]
+    fn toolbar_state_round_trips() {
+        let all = Route::Quotes(QuoteListQuery {
+            search: Some("café & crème".to_string()),
+            tag: Some("wit".to_string()),
+            author: Some("Oscar Wilde".to_string()),
+            sort: QuoteSort::Oldest,
+            limit: Some(50),
+        });
+        assert_eq!(all.fragment(), "#/?search=caf%C3%A9%20%26%20cr%C3%A8me&tag=wit&author=Oscar%20Wilde&sort=oldest&limit=50");
+        assert_eq!(Route::parse(&all.fragment()), all);
+
+        let searched = Route::Quotes(QuoteListQuery { search: Some("a=b?".to_string()), limit: Some(20), ..list() });
+        assert_eq!(Route::parse(&searched.fragment()), searched);
+    }
+
+    #[test]
+    fn older_search_links_still_parse() {
+        let expected = Route::Quotes(QuoteListQuery { search: Some("hello world".to_string()), ..list() });
+        assert_eq!(Route::parse("#/search?q=hello+world"), expected);
+        assert_eq!(Route::parse("#/?q=%20hello%20world%20"), expected);
+    }
+
+    #[test]
+    fn unsupported_page_sizes_are_dropped() {
+        assert_eq!(Route::parse("#/?limit=100").list_query().and_then(|list| list.limit), Some(100));
+        for limit in ["7", "1000", "-10", "ten"] {
+            let route = Route::parse(&format!("#/?limit={}", limit));
+            assert_eq!(route.list_query().and_then(|list| list.limit), None, "{:?}", limit);
         }
     }
 }
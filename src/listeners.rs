@@ -0,0 +1,76 @@
+//! Ownership of the closures behind DOM event handlers.
+//!
+//! A `Closure` handed to the browser has to outlive every call to it, and
+//! `forget` makes that forever. Instead, each listener is registered under
+//! the `Scope` of the markup it serves and kept here until that markup is
+//! replaced: clearing a scope removes its listeners from their targets and
+//! frees the closures.
+
+use std::{ cell::RefCell, collections::HashMap };
+use wasm_bindgen::prelude::*;
+use web_sys::{ Event, EventTarget };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Elements that last as long as the page, such as `window` and the
+    /// containers in `index.html`. Never cleared.
+    Page,
+    /// Whatever is rendered into `#modal-content`.
+    Modal,
+    /// Buttons in the modal's `#translation-toggle`.
+    Translations,
+    /// Buttons in the modal's `#collection-options`.
+    CollectionOptions,
+    /// The current `/ws` connection.
+    LiveSocket,
+}
+
+impl Scope {
+    /// Scopes whose elements sit inside this one's, and go when it does.
+    fn nested(self) -> &'static [Scope] {
+        match self {
+            Scope::Modal => &[Scope::Translations, Scope::CollectionOptions],
+            _ => &[],
+        }
+    }
+}
+
+struct Listener {
+    target: EventTarget,
+    event: &'static str,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self.target.remove_event_listener_with_callback(self.event, self.closure.as_ref().unchecked_ref());
+    }
+}
+
+thread_local! {
+    static LISTENERS: RefCell<HashMap<Scope, Vec<Listener>>> = RefCell::new(HashMap::new());
+}
+
+/// Calls `handler` for every `event` on `target` until `scope` is cleared.
+pub fn listen(scope: Scope, target: &EventTarget, event: &'static str, handler: impl FnMut(Event) + 'static) {
+    let closure = Closure::<dyn FnMut(Event)>::new(handler);
+    if target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).is_err() {
+        return;
+    }
+
+    let listener = Listener {
+        target: target.clone(),
+        event,
+        closure,
+    };
+    LISTENERS.with(|listeners| listeners.borrow_mut().entry(scope).or_default().push(listener));
+}
+
+/// Removes and frees every listener in `scope` and the scopes nested in it.
+/// Call it right before the scope's markup is replaced.
+pub fn clear(scope: Scope) {
+    LISTENERS.with(|listeners| listeners.borrow_mut().remove(&scope));
+    for &nested in scope.nested() {
+        clear(nested);
+    }
+}
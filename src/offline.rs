@@ -0,0 +1,164 @@
+//! What the page keeps in IndexedDB to keep working without the API.
+//!
+//! `responses` holds the JSON of recent quote reads, keyed by API path, so
+//! the client can answer from it when a request cannot reach the server.
+//! `outbox` holds quotes submitted while offline, keyed by their
+//! `submission_id`, until they have been posted.
+
+use serde::{ Deserialize, Serialize };
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ window, IdbDatabase, IdbRequest, IdbTransactionMode };
+
+const DB_NAME: &str = "rusted_quotes";
+const DB_VERSION: u32 = 1;
+const RESPONSES: &str = "responses";
+const OUTBOX: &str = "outbox";
+
+thread_local! {
+    static DB: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// A quote waiting to be posted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingQuote {
+    pub submission_id: String,
+    pub quote: String,
+    pub author: Option<String>,
+    pub queued_at: String,
+}
+
+/// The handlers `complete` sets on a request. Only one of them ever runs, so
+/// both are owned here and unhooked and freed together once the request has
+/// settled, or its caller stopped waiting.
+struct Handlers {
+    request: IdbRequest,
+    _on_success: Closure<dyn FnMut()>,
+    _on_error: Closure<dyn FnMut()>,
+}
+
+impl Drop for Handlers {
+    fn drop(&mut self) {
+        self.request.set_onsuccess(None);
+        self.request.set_onerror(None);
+    }
+}
+
+/// Resolves with the request's result once it succeeds.
+async fn complete(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let mut handlers = None;
+    let promise = js_sys::Promise::new(
+        &mut (|resolve: js_sys::Function, reject: js_sys::Function| {
+            let on_success = Closure::<dyn FnMut()>::new(move || {
+                let _ = resolve.call0(&JsValue::NULL);
+            });
+            let on_error = Closure::<dyn FnMut()>::new(move || {
+                let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("IndexedDB request failed"));
+            });
+            request.set_onsuccess(Some(on_success.as_ref().unchecked_ref()));
+            request.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+            handlers = Some(Handlers { request: request.clone(), _on_success: on_success, _on_error: on_error });
+        })
+    );
+    JsFuture::from(promise).await?;
+    drop(handlers);
+    request.result()
+}
+
+async fn database() -> Result<IdbDatabase, JsValue> {
+    if let Some(db) = DB.with(|db| db.borrow().clone()) {
+        return Ok(db);
+    }
+
+    let factory = window()
+        .ok_or_else(|| JsValue::from_str("no window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB unavailable"))?;
+    let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    let opening = request.clone();
+    let on_upgrade = Closure::<dyn FnMut()>::new(move || {
+        let Some(db) = opening.result().ok().and_then(|db| db.dyn_into::<IdbDatabase>().ok()) else {
+            return;
+        };
+        for store in [RESPONSES, OUTBOX] {
+            if !db.object_store_names().contains(store) {
+                let _ = db.create_object_store(store);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+    // An upgrade, if any, runs before the open succeeds or fails.
+    let opened = complete(&request).await;
+    request.set_onupgradeneeded(None);
+    drop(on_upgrade);
+    let db: IdbDatabase = opened?.dyn_into()?;
+    DB.with(|cached| *cached.borrow_mut() = Some(db.clone()));
+    Ok(db)
+}
+
+async fn put(store: &str, key: &str, value: &str) -> Result<(), JsValue> {
+    let db = database().await?;
+    let request = db
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?
+        .object_store(store)?
+        .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?;
+    complete(&request).await.map(|_| ())
+}
+
+async fn get(store: &str, key: &str) -> Result<Option<String>, JsValue> {
+    let db = database().await?;
+    let request = db
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readonly)?
+        .object_store(store)?
+        .get(&JsValue::from_str(key))?;
+    Ok(complete(&request).await?.as_string())
+}
+
+async fn get_all(store: &str) -> Result<Vec<String>, JsValue> {
+    let db = database().await?;
+    let request = db
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readonly)?
+        .object_store(store)?
+        .get_all()?;
+    let values: js_sys::Array = complete(&request).await?.dyn_into()?;
+    Ok(values.iter().filter_map(|value| value.as_string()).collect())
+}
+
+async fn delete(store: &str, key: &str) -> Result<(), JsValue> {
+    let db = database().await?;
+    let request = db
+        .transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?
+        .object_store(store)?
+        .delete(&JsValue::from_str(key))?;
+    complete(&request).await.map(|_| ())
+}
+
+pub async fn save_response(path: &str, json: &str) -> Result<(), JsValue> {
+    put(RESPONSES, path, json).await
+}
+
+pub async fn saved_response(path: &str) -> Result<Option<String>, JsValue> {
+    get(RESPONSES, path).await
+}
+
+pub async fn queue_submission(pending: &PendingQuote) -> Result<(), JsValue> {
+    let json = serde_json::to_string(pending).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    put(OUTBOX, &pending.submission_id, &json).await
+}
+
+/// Oldest first, so quotes are posted in the order they were written.
+pub async fn pending_submissions() -> Result<Vec<PendingQuote>, JsValue> {
+    let mut pending: Vec<PendingQuote> = get_all(OUTBOX).await?
+        .iter()
+        .filter_map(|json| serde_json::from_str(json).ok())
+        .collect();
+    pending.sort_by(|a, b| a.queued_at.cmp(&b.queued_at));
+    Ok(pending)
+}
+
+pub async fn remove_submission(submission_id: &str) -> Result<(), JsValue> {
+    delete(OUTBOX, submission_id).await
+}
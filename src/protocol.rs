@@ -0,0 +1,62 @@
+//! Messages exchanged over the `/api/ws` WebSocket.
+//!
+//! This file is compiled into both the WASM client and the server (the
+//! server includes it with `#[path]`), so the two sides cannot drift apart.
+//! It must only depend on `serde`. Every message is a JSON object tagged by
+//! its `type` field, e.g. `{"type":"view","quote_id":"..."}`.
+
+use serde::{ Deserialize, Serialize };
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// The client opened this quote. Replaces whatever it viewed before.
+    View {
+        quote_id: String,
+    },
+    /// The client closed the quote it was viewing.
+    Leave,
+    /// Upgrades the connection to receive moderation notices.
+    Authenticate {
+        token: String,
+    },
+    Ping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    LikeCount {
+        quote_id: String,
+        like_count: u32,
+    },
+    /// How many connections currently have the quote open.
+    Presence {
+        quote_id: String,
+        viewers: u32,
+    },
+    /// Sent to admin connections only.
+    Moderation {
+        action: ModerationAction,
+        quote_id: Option<String>,
+        detail: Option<String>,
+    },
+    Authenticated {
+        admin: bool,
+    },
+    Pong,
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// A submission was refused by the content filter.
+    Rejected,
+    Edited,
+    Reverted,
+    Deleted,
+    Restored,
+}
@@ -0,0 +1,474 @@
+//! Shareable quote cards.
+//!
+//! `GET /api/quotes/:id/card.svg` and `card.png` draw the quote, word-wrapped
+//! to fit, with its author in the site's neon-on-dark theme. `?size=` picks a
+//! preset for the usual social media image slots. Both formats share one
+//! layout; the PNG is rasterized in-process by [`crate::raster`] using the
+//! bundled DejaVu Sans Mono, so the server needs no image libraries.
+//!
+//! `/share/:id` is a small HTML page carrying Open Graph and Twitter card
+//! tags that point at the PNG, so shared links unfurl with the card.
+
+use axum::{
+    extract::{ Path, Query, State },
+    http::{ header::{ CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH }, HeaderMap, StatusCode },
+    response::{ Html, IntoResponse, Response },
+};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+use crate::{
+    app_url,
+    csp,
+    embed::discovery_url,
+    escape_html,
+    fetch_quote,
+    public_url,
+    raster::{ Canvas, Font, Rgb },
+    AppState,
+    Quote,
+};
+
+const REGULAR_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf");
+/// What SVG viewers should try before falling back to the bundled font.
+const FONT_FAMILY: &str = "'JetBrains Mono', 'DejaVu Sans Mono', Consolas, monospace";
+
+const BACKGROUND_TOP: Rgb = [0x02, 0x06, 0x17];
+const BACKGROUND_BOTTOM: Rgb = [0x0f, 0x17, 0x2a];
+const TEXT: Rgb = [0xf1, 0xf5, 0xf9];
+const MUTED: Rgb = [0x94, 0xa3, 0xb8];
+const CYAN: Rgb = [0x00, 0xff, 0xff];
+const PURPLE: Rgb = [0x8b, 0x5c, 0xf6];
+const PINK: Rgb = [0xff, 0x00, 0x80];
+
+const LINE_HEIGHT: f32 = 1.35;
+/// Cards are cached by quote version, so they can be kept for a while.
+const CACHE_SECONDS: u32 = 86400;
+
+struct Fonts {
+    regular: Font,
+    bold: Font,
+}
+
+fn fonts() -> &'static Fonts {
+    static FONTS: OnceLock<Fonts> = OnceLock::new();
+    FONTS.get_or_init(|| Fonts {
+        regular: Font::parse(REGULAR_FONT).expect("bundled regular font is valid"),
+        bold: Font::parse(BOLD_FONT).expect("bundled bold font is valid"),
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardSize {
+    /// Open Graph / Facebook / LinkedIn link previews.
+    #[default]
+    Og,
+    /// Twitter/X summary_large_image.
+    Twitter,
+    /// Instagram and Mastodon feed posts.
+    Square,
+    /// Instagram and WhatsApp stories.
+    Story,
+}
+
+impl CardSize {
+    pub fn dimensions(self) -> (u32, u32) {
+        match self {
+            CardSize::Og => (1200, 630),
+            CardSize::Twitter => (1200, 675),
+            CardSize::Square => (1080, 1080),
+            CardSize::Story => (1080, 1920),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            CardSize::Og => "og",
+            CardSize::Twitter => "twitter",
+            CardSize::Square => "square",
+            CardSize::Story => "story",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CardQuery {
+    #[serde(default)]
+    size: CardSize,
+}
+
+/// Positions shared by the SVG and PNG renderings, in pixels.
+struct Layout {
+    width: f32,
+    height: f32,
+    padding: f32,
+    frame: f32,
+    accent_height: f32,
+    mark_size: f32,
+    mark_baseline: f32,
+    quote_size: f32,
+    quote_baselines: Vec<(String, f32)>,
+    author: Option<(String, f32)>,
+    author_size: f32,
+    footer_size: f32,
+    footer_baseline: f32,
+}
+
+/// Greedy word wrap by measured width. Words wider than a line are split.
+fn wrap(font: &Font, text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if font.measure(&candidate, size) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if font.measure(&line, size) > max_width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn layout(quote: &Quote, size: CardSize) -> Layout {
+    let fonts = fonts();
+    let (width, height) = size.dimensions();
+    let (width, height) = (width as f32, height as f32);
+    let base = width.min(height);
+
+    let padding = base * 0.1;
+    let footer_size = base * 0.026;
+    let footer_baseline = height - padding * 0.55;
+    let author_size = base * 0.042;
+    let mark_size = base * 0.16;
+    let mark_baseline = padding + fonts.bold.ascent(mark_size) * 0.75;
+
+    let text_width = width - 2.0 * padding;
+    let text_top = mark_baseline + base * 0.02;
+    let author_baseline = footer_baseline - footer_size * 2.5;
+    let text_bottom = author_baseline - author_size * 2.0;
+    let available = text_bottom - text_top;
+
+    // The largest size at which the whole quote fits; very long quotes are
+    // cut at the smallest size instead.
+    let largest = base * 0.075;
+    let smallest = base * 0.032;
+    let mut quote_size = largest;
+    let mut lines = wrap(&fonts.regular, &quote.quote, quote_size, text_width);
+    while quote_size > smallest && lines.len() as f32 * quote_size * LINE_HEIGHT > available {
+        quote_size = (quote_size * 0.92).max(smallest);
+        lines = wrap(&fonts.regular, &quote.quote, quote_size, text_width);
+    }
+    let line_height = quote_size * LINE_HEIGHT;
+    let max_lines = ((available / line_height).floor() as usize).max(1);
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            while !last.is_empty() && fonts.regular.measure(&format!("{}…", last), quote_size) > text_width {
+                last.pop();
+            }
+            last.push('…');
+        }
+    }
+
+    let block = lines.len() as f32 * line_height;
+    let first_baseline = text_top + (available - block) / 2.0 + fonts.regular.ascent(quote_size);
+    let quote_baselines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| (line, first_baseline + i as f32 * line_height))
+        .collect();
+
+    let author = quote.author
+        .as_deref()
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .map(|author| {
+            let mut name = format!("— {}", author);
+            while fonts.bold.measure(&name, author_size) > text_width && name.chars().count() > 3 {
+                name.pop();
+            }
+            (name, author_baseline)
+        });
+
+    Layout {
+        width,
+        height,
+        padding,
+        frame: padding * 0.4,
+        accent_height: (base * 0.012).max(4.0),
+        mark_size,
+        mark_baseline,
+        quote_size,
+        quote_baselines,
+        author,
+        author_size,
+        footer_size,
+        footer_baseline,
+    }
+}
+
+fn footer_text(quote: &Quote) -> String {
+    let tags: Vec<String> = quote.tags
+        .iter()
+        .take(3)
+        .map(|tag| format!("#{}", tag))
+        .collect();
+    if tags.is_empty() { "RUSTED QUOTES".to_string() } else { format!("RUSTED QUOTES  {}", tags.join(" ")) }
+}
+
+fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn render_svg(quote: &Quote, size: CardSize) -> String {
+    let l = layout(quote, size);
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="{family}">
+<defs>
+<linearGradient id="background" x1="0" y1="0" x2="0" y2="1"><stop offset="0" stop-color="{top}"/><stop offset="1" stop-color="{bottom}"/></linearGradient>
+<linearGradient id="accent" x1="0" y1="0" x2="1" y2="0"><stop offset="0" stop-color="{cyan}"/><stop offset="0.5" stop-color="{purple}"/><stop offset="1" stop-color="{pink}"/></linearGradient>
+<filter id="glow" x="-10%" y="-10%" width="120%" height="120%"><feGaussianBlur stdDeviation="6" result="blur"/><feMerge><feMergeNode in="blur"/><feMergeNode in="SourceGraphic"/></feMerge></filter>
+</defs>
+<rect width="{w}" height="{h}" fill="url(#background)"/>
+<rect width="{w}" height="{accent}" fill="url(#accent)"/>
+<rect x="{frame}" y="{frame}" width="{fw}" height="{fh}" fill="none" stroke="{cyan}" stroke-opacity="0.35" stroke-width="2" filter="url(#glow)"/>
+<text x="{pad}" y="{mark_y}" font-size="{mark_size}" font-weight="bold" fill="{cyan}" filter="url(#glow)">“</text>
+"#,
+        w = l.width,
+        h = l.height,
+        family = FONT_FAMILY,
+        top = hex(BACKGROUND_TOP),
+        bottom = hex(BACKGROUND_BOTTOM),
+        cyan = hex(CYAN),
+        purple = hex(PURPLE),
+        pink = hex(PINK),
+        accent = l.accent_height,
+        frame = l.frame,
+        fw = l.width - 2.0 * l.frame,
+        fh = l.height - 2.0 * l.frame,
+        pad = l.padding,
+        mark_y = l.mark_baseline,
+        mark_size = l.mark_size
+    );
+
+    svg.push_str(
+        &format!(r#"<text font-size="{:.1}" fill="{}" xml:space="preserve">"#, l.quote_size, hex(TEXT))
+    );
+    for (line, baseline) in &l.quote_baselines {
+        svg.push_str(&format!(r#"<tspan x="{}" y="{:.1}">{}</tspan>"#, l.padding, baseline, escape_html(line)));
+    }
+    svg.push_str("</text>\n");
+
+    if let Some((author, baseline)) = &l.author {
+        svg.push_str(
+            &format!(
+                r#"<text x="{}" y="{:.1}" font-size="{:.1}" font-weight="bold" fill="{}">{}</text>
+"#,
+                l.padding,
+                baseline,
+                l.author_size,
+                hex(PINK),
+                escape_html(author)
+            )
+        );
+    }
+    svg.push_str(
+        &format!(
+            r#"<text x="{}" y="{:.1}" font-size="{:.1}" letter-spacing="2" fill="{}">{}</text>
+</svg>
+"#,
+            l.padding,
+            l.footer_baseline,
+            l.footer_size,
+            hex(MUTED),
+            escape_html(&footer_text(quote))
+        )
+    );
+    svg
+}
+
+fn render_png(quote: &Quote, size: CardSize) -> Vec<u8> {
+    let fonts = fonts();
+    let l = layout(quote, size);
+    let mut canvas = Canvas::new(l.width as usize, l.height as usize);
+
+    canvas.vertical_gradient(BACKGROUND_TOP, BACKGROUND_BOTTOM);
+    canvas.horizontal_gradient(0.0, 0.0, l.width, l.accent_height, &[CYAN, PURPLE, PINK]);
+
+    // The frame's glow: a few widening, fading outlines around a 2px line.
+    let (x, y, w, h) = (l.frame, l.frame, l.width - 2.0 * l.frame, l.height - 2.0 * l.frame);
+    for (spread, alpha) in [(6.0, 0.04), (4.0, 0.06), (2.0, 0.1), (0.0, 0.35)] {
+        let thickness = 2.0 + 2.0 * spread;
+        let (x, y, w, h) = (x - spread, y - spread, w + 2.0 * spread, h + 2.0 * spread);
+        canvas.fill_rect(x, y, w, thickness, CYAN, alpha);
+        canvas.fill_rect(x, y + h - thickness, w, thickness, CYAN, alpha);
+        canvas.fill_rect(x, y + thickness, thickness, h - 2.0 * thickness, CYAN, alpha);
+        canvas.fill_rect(x + w - thickness, y + thickness, thickness, h - 2.0 * thickness, CYAN, alpha);
+    }
+
+    canvas.draw_text(&fonts.bold, "“", l.padding, l.mark_baseline, l.mark_size, CYAN);
+    for (line, baseline) in &l.quote_baselines {
+        canvas.draw_text(&fonts.regular, line, l.padding, *baseline, l.quote_size, TEXT);
+    }
+    if let Some((author, baseline)) = &l.author {
+        canvas.draw_text(&fonts.bold, author, l.padding, *baseline, l.author_size, PINK);
+    }
+    canvas.draw_text(&fonts.regular, &footer_text(quote), l.padding, l.footer_baseline, l.footer_size, MUTED);
+
+    canvas.to_png()
+}
+
+/// Changes whenever the quote is edited, so caches can revalidate cheaply.
+fn card_etag(quote: &Quote, size: CardSize, format: &str) -> String {
+    format!("\"{}-{}-{}-{}\"", quote.id, quote.updated_at.timestamp(), size.name(), format)
+}
+
+async fn load_card_quote(state: &AppState, id: &str) -> Result<Quote, StatusCode> {
+    fetch_quote(&state.pool, id).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag))
+}
+
+fn card_response(headers: &HeaderMap, etag: String, content_type: &'static str, render: impl FnOnce() -> Vec<u8>) -> Response {
+    let cache_control = format!("public, max-age={}", CACHE_SECONDS);
+    if not_modified(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag), (CACHE_CONTROL, cache_control)]).into_response();
+    }
+    ([(CONTENT_TYPE, content_type.to_string()), (ETAG, etag), (CACHE_CONTROL, cache_control)], render()).into_response()
+}
+
+pub async fn card_svg(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<CardQuery>,
+    headers: HeaderMap
+) -> Result<Response, StatusCode> {
+    let quote = load_card_quote(&state, &id).await?;
+    let etag = card_etag(&quote, params.size, "svg");
+    let mut response = card_response(&headers, etag, "image/svg+xml", || render_svg(&quote, params.size).into_bytes());
+    let (name, policy) = csp::locked();
+    response.headers_mut().insert(name, policy.parse().expect("the policy is a valid header value"));
+    Ok(response)
+}
+
+pub async fn card_png(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<CardQuery>,
+    headers: HeaderMap
+) -> Result<Response, StatusCode> {
+    let quote = load_card_quote(&state, &id).await?;
+    let etag = card_etag(&quote, params.size, "png");
+    if not_modified(&headers, &etag) {
+        return Ok(card_response(&headers, etag, "image/png", Vec::new));
+    }
+
+    // Rasterizing takes a while for large sizes; keep it off the async
+    // workers.
+    let size = params.size;
+    let png = tokio::task::spawn_blocking(move || render_png(&quote, size)).await.map_err(|e| {
+        eprintln!("Card rendering failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(card_response(&headers, etag, "image/png", || png))
+}
+
+const SHARE_STYLE: &str =
+    "
+body { margin: 0; min-height: 100vh; display: flex; flex-direction: column; align-items: center; justify-content: center; gap: 1.5rem; background: #020617; color: #f1f5f9; font-family: 'JetBrains Mono', Consolas, monospace; }
+img { max-width: min(90vw, 1200px); height: auto; border: 1px solid rgba(0, 255, 255, 0.3); }
+a { color: #00ffff; }
+";
+
+/// The public page for a quote, with preview tags for link unfurling.
+pub fn share_url(id: &str) -> String {
+    format!("{}/share/{}", public_url(), id)
+}
+
+pub async fn share_page(
+    State(state): State<AppState>,
+    Path(id): Path<String>
+) -> Result<impl IntoResponse, StatusCode> {
+    let quote = load_card_quote(&state, &id).await?;
+    let (width, height) = CardSize::Og.dimensions();
+    let image = format!("{}/api/quotes/{}/card.png", public_url(), quote.id);
+    let title = match &quote.author {
+        Some(author) => format!("Quote by {}", author),
+        None => "Quote".to_string(),
+    };
+    let description: String = quote.quote.chars().take(200).collect();
+
+    let page = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>{title} | Rusted Quotes</title>
+<meta name="description" content="{description}">
+<meta property="og:type" content="article">
+<meta property="og:site_name" content="Rusted Quotes">
+<meta property="og:title" content="{title}">
+<meta property="og:description" content="{description}">
+<meta property="og:url" content="{url}">
+<meta property="og:image" content="{image}">
+<meta property="og:image:type" content="image/png">
+<meta property="og:image:width" content="{width}">
+<meta property="og:image:height" content="{height}">
+<meta property="og:image:alt" content="{description}">
+<meta name="twitter:card" content="summary_large_image">
+<meta name="twitter:title" content="{title}">
+<meta name="twitter:description" content="{description}">
+<meta name="twitter:image" content="{image}?size=twitter">
+<link rel="canonical" href="{url}">
+<link rel="alternate" type="application/json+oembed" href="{oembed}" title="{title}">
+<style>{style}</style>
+</head>
+<body>
+<img src="{svg}" alt="{description}" width="{width}" height="{height}">
+<a href="{app}">Open Rusted Quotes</a>
+</body>
+</html>
+"#,
+        lang = escape_html(quote.language.as_deref().unwrap_or("en")),
+        title = escape_html(&title),
+        description = escape_html(&description),
+        url = escape_html(&share_url(&quote.id)),
+        oembed = escape_html(&discovery_url(&share_url(&quote.id))),
+        image = escape_html(&image),
+        svg = escape_html(&format!("{}/api/quotes/{}/card.svg", public_url(), quote.id)),
+        app = escape_html(&format!("{}/#/quotes/{}", app_url(), quote.id)),
+        style = SHARE_STYLE,
+        width = width,
+        height = height
+    );
+
+    Ok(([csp::page(SHARE_STYLE, &public_url())], Html(page)))
+}
+
@@ -0,0 +1,354 @@
+//! Source attribution.
+//!
+//! A quote can record where it came from: the work, its year, a URL, the page
+//! and whether an admin has checked it. `GET /api/quotes/:id/cite` formats
+//! that into a reference in one of the common styles. Author names are used
+//! exactly as stored, since they are free text and not reliably "First Last".
+
+use axum::{ extract::{ Path, Query, State }, http::StatusCode, response::Json };
+use serde::{ Deserialize, Serialize };
+use sqlx::{ mysql::MySqlRow, Executor, MySql, Row };
+
+use crate::{ contains_inappropriate_content, AppState, QUOTE_COLUMNS };
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Source {
+    /// Title of the book, speech, article, etc. the quote is taken from.
+    pub title: Option<String>,
+    /// Negative years are BCE.
+    pub year: Option<i32>,
+    pub url: Option<String>,
+    /// A single page ("42") or a range ("42-43").
+    pub page: Option<String>,
+    /// Set by admins once the attribution has been checked.
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    #[default]
+    Apa,
+    Mla,
+    Chicago,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CiteQuery {
+    style: Option<CitationStyle>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Citation {
+    id: String,
+    style: CitationStyle,
+    citation: String,
+    verified: bool,
+}
+
+fn clean(value: Option<String>, max_len: usize) -> Result<Option<String>, StatusCode> {
+    match value.map(|value| value.trim().to_string()) {
+        Some(value) if value.is_empty() => Ok(None),
+        Some(value) if value.chars().count() > max_len || contains_inappropriate_content(&value) => {
+            Err(StatusCode::BAD_REQUEST)
+        }
+        value => Ok(value),
+    }
+}
+
+/// Trims the fields and rejects anything that would not make a usable
+/// citation. Returns `None` when nothing but the flag was given.
+pub fn normalize_source(source: Source) -> Result<Option<Source>, StatusCode> {
+    let url = clean(source.url, 2048)?;
+    if url.as_ref().is_some_and(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if source.year.is_some_and(|year| year == 0 || !(-9999..=9999).contains(&year)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let source = Source {
+        title: clean(source.title, 255)?,
+        year: source.year,
+        url,
+        page: clean(source.page, 50)?,
+        verified: source.verified,
+    };
+
+    if source.title.is_none() && source.year.is_none() && source.url.is_none() && source.page.is_none() {
+        Ok(None)
+    } else {
+        Ok(Some(source))
+    }
+}
+
+pub fn source_from_row(row: &MySqlRow) -> Option<Source> {
+    let source = Source {
+        title: row.get("source_title"),
+        year: row.get("source_year"),
+        url: row.get("source_url"),
+        page: row.get("source_page"),
+        verified: row.get("source_verified"),
+    };
+
+    if source.title.is_none() && source.year.is_none() && source.url.is_none() && source.page.is_none() {
+        None
+    } else {
+        Some(source)
+    }
+}
+
+pub async fn save_source<'e, E>(executor: E, quote_id: &str, source: Option<&Source>) -> Result<(), sqlx::Error>
+    where E: Executor<'e, Database = MySql>
+{
+    let empty = Source::default();
+    let source = source.unwrap_or(&empty);
+
+    sqlx::query(
+        "UPDATE quotes
+         SET source_title = ?, source_year = ?, source_url = ?, source_page = ?, source_verified = ?
+         WHERE id = ?"
+    )
+        .bind(&source.title)
+        .bind(source.year)
+        .bind(&source.url)
+        .bind(&source.page)
+        .bind(source.verified)
+        .bind(quote_id)
+        .execute(executor).await?;
+
+    Ok(())
+}
+
+/// Adds a final period unless the text already ends a sentence.
+fn sentence(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) { text.to_string() } else { format!("{}.", text) }
+}
+
+fn year_text(year: i32) -> String {
+    if year < 0 { format!("{} BCE", -year) } else { year.to_string() }
+}
+
+fn page_text(page: &str) -> String {
+    let prefix = if page.contains(['-', '–', ',']) { "pp." } else { "p." };
+    format!("{} {}", prefix, page)
+}
+
+fn format_citation(style: CitationStyle, author: Option<&str>, source: &Source) -> String {
+    let title = source.title.as_deref().unwrap_or("Untitled");
+    let year = source.year.map(year_text);
+    let mut parts: Vec<String> = Vec::new();
+
+    match style {
+        // Author. (Year). Title (p. 12). URL
+        CitationStyle::Apa => {
+            let title = match &source.page {
+                Some(page) => format!("{} ({})", title, page_text(page)),
+                None => title.to_string(),
+            };
+            let year = format!("({}).", year.as_deref().unwrap_or("n.d."));
+            match author {
+                Some(author) => {
+                    parts.push(sentence(author));
+                    parts.push(year);
+                    parts.push(sentence(&title));
+                }
+                None => {
+                    parts.push(sentence(&title));
+                    parts.push(year);
+                }
+            }
+            parts.extend(source.url.clone());
+        }
+        // Author. Title. Year, p. 12, URL.
+        CitationStyle::Mla => {
+            parts.extend(author.map(sentence));
+            parts.push(sentence(title));
+            let details: Vec<String> = year
+                .into_iter()
+                .chain(source.page.as_deref().map(page_text))
+                .chain(source.url.as_deref().map(|url| url.trim_start_matches("https://").trim_start_matches("http://").to_string()))
+                .collect();
+            if !details.is_empty() {
+                parts.push(sentence(&details.join(", ")));
+            }
+        }
+        // Author, Title (Year), 12, URL.
+        CitationStyle::Chicago => {
+            let mut note = match author {
+                Some(author) => format!("{}, {}", author, title),
+                None => title.to_string(),
+            };
+            if let Some(year) = year {
+                note.push_str(&format!(" ({})", year));
+            }
+            if let Some(page) = &source.page {
+                note.push_str(&format!(", {}", page));
+            }
+            if let Some(url) = &source.url {
+                note.push_str(&format!(", {}", url));
+            }
+            parts.push(sentence(&note));
+        }
+    }
+
+    parts.join(" ")
+}
+
+pub async fn cite_quote(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<CiteQuery>
+) -> Result<Json<Citation>, StatusCode> {
+    let row = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL", QUOTE_COLUMNS))
+        .bind(&id)
+        .fetch_optional(&state.pool).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let author: Option<String> = row.get("author");
+    // Without at least a work title there is nothing to cite.
+    let source = source_from_row(&row)
+        .filter(|source| source.title.is_some())
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let style = params.style.unwrap_or_default();
+
+    Ok(
+        Json(Citation {
+            id,
+            style,
+            citation: format_citation(style, author.as_deref(), &source),
+            verified: source.verified,
+        })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dorian() -> Source {
+        Source {
+            title: Some("The Picture of Dorian Gray".to_string()),
+            year: Some(1890),
+            url: Some("https://example.com/dorian".to_string()),
+            page: Some("42-43".to_string()),
+            verified: false,
+        }
+    }
+
+    fn titled(title: &str) -> Source {
+        Source { title: Some(title.to_string()), ..Source::default() }
+    }
+
+    #[test]
+    fn apa_puts_the_year_after_the_author() {
+        assert_eq!(
+            format_citation(CitationStyle::Apa, Some("Oscar Wilde"), &dorian()),
+            "Oscar Wilde. (1890). The Picture of Dorian Gray (pp. 42-43). https://example.com/dorian"
+        );
+    }
+
+    #[test]
+    fn apa_without_an_author_leads_with_the_title() {
+        let source = Source { page: Some("7".to_string()), ..titled("The Picture of Dorian Gray") };
+        assert_eq!(format_citation(CitationStyle::Apa, None, &source), "The Picture of Dorian Gray (p. 7). (n.d.).");
+    }
+
+    #[test]
+    fn mla_strips_the_url_scheme() {
+        assert_eq!(
+            format_citation(CitationStyle::Mla, Some("Oscar Wilde"), &dorian()),
+            "Oscar Wilde. The Picture of Dorian Gray. 1890, pp. 42-43, example.com/dorian."
+        );
+        let source = Source { url: Some("http://example.com/a".to_string()), ..Source::default() };
+        assert_eq!(format_citation(CitationStyle::Mla, None, &source), "Untitled. example.com/a.");
+    }
+
+    #[test]
+    fn chicago_is_a_single_note() {
+        assert_eq!(
+            format_citation(CitationStyle::Chicago, Some("Oscar Wilde"), &dorian()),
+            "Oscar Wilde, The Picture of Dorian Gray (1890), 42-43, https://example.com/dorian."
+        );
+        assert_eq!(format_citation(CitationStyle::Chicago, None, &titled("What Is Art?")), "What Is Art?");
+    }
+
+    #[test]
+    fn negative_years_are_bce() {
+        let source = Source { year: Some(-500), page: Some("3".to_string()), ..titled("The Art of War") };
+        assert_eq!(
+            format_citation(CitationStyle::Chicago, Some("Sun Tzu"), &source),
+            "Sun Tzu, The Art of War (500 BCE), 3."
+        );
+        assert_eq!(
+            format_citation(CitationStyle::Apa, Some("Sun Tzu"), &source),
+            "Sun Tzu. (500 BCE). The Art of War (p. 3)."
+        );
+    }
+
+    #[test]
+    fn sentences_are_not_closed_twice() {
+        let source = Source { year: Some(1963), ..titled("Letter from Birmingham Jail") };
+        assert_eq!(
+            format_citation(CitationStyle::Mla, Some("Martin Luther King Jr."), &source),
+            "Martin Luther King Jr. Letter from Birmingham Jail. 1963."
+        );
+    }
+
+    #[test]
+    fn sources_are_trimmed() {
+        let source = Source {
+            title: Some("  Meditations ".to_string()),
+            year: Some(-9999),
+            url: Some(" https://example.com ".to_string()),
+            page: Some("".to_string()),
+            verified: true,
+        };
+        let source = normalize_source(source).unwrap().unwrap();
+        assert_eq!(source.title.as_deref(), Some("Meditations"));
+        assert_eq!(source.url.as_deref(), Some("https://example.com"));
+        assert_eq!(source.page, None);
+        assert!(source.verified);
+    }
+
+    #[test]
+    fn empty_sources_are_none() {
+        assert!(normalize_source(Source::default()).unwrap().is_none());
+        let blank = Source { title: Some("   ".to_string()), verified: true, ..Source::default() };
+        assert!(normalize_source(blank).unwrap().is_none());
+    }
+
+    #[test]
+    fn impossible_years_are_rejected() {
+        for year in [0, 10_000, -10_000] {
+            let source = Source { year: Some(year), ..titled("Title") };
+            assert_eq!(normalize_source(source).err(), Some(StatusCode::BAD_REQUEST), "{}", year);
+        }
+    }
+
+    #[test]
+    fn only_web_urls_are_accepted() {
+        for url in ["ftp://example.com/a", "javascript:alert(1)", "example.com"] {
+            let source = Source { url: Some(url.to_string()), ..Source::default() };
+            assert_eq!(normalize_source(source).err(), Some(StatusCode::BAD_REQUEST), "{}", url);
+        }
+    }
+
+    #[test]
+    fn overlong_fields_are_rejected() {
+        assert!(normalize_source(titled(&"t".repeat(255))).is_ok());
+        assert_eq!(normalize_source(titled(&"t".repeat(256))).err(), Some(StatusCode::BAD_REQUEST));
+
+        let page = Source { page: Some("1".repeat(51)), ..Source::default() };
+        assert_eq!(normalize_source(page).err(), Some(StatusCode::BAD_REQUEST));
+
+        let url = Source { url: Some(format!("https://example.com/{}", "a".repeat(2048))), ..Source::default() };
+        assert_eq!(normalize_source(url).err(), Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,520 @@
+//! Named, ordered collections of quotes ("onboarding quotes", "retro
+//! openers").
+//!
+//! Collections belong to the anonymous client that created them, identified
+//! by its `X-Client-Id`. Public collections can be read by anyone through
+//! their slug; private ones only exist for their owner.
+
+use axum::{ extract::{ Path, Query, State }, http::{ HeaderMap, StatusCode }, response::Json };
+use chrono::{ DateTime, Utc };
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use sqlx::{ mysql::{ MySqlPool, MySqlRow }, MySql, Row, Transaction };
+use uuid::Uuid;
+
+use crate::{ client_id, contains_inappropriate_content, quote_from_row, AppState, Quote, QUOTE_COLUMNS };
+
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Debug, Serialize)]
+pub struct Collection {
+    id: String,
+    slug: String,
+    name: String,
+    description: Option<String>,
+    is_public: bool,
+    quote_count: i64,
+    owned: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionDetail {
+    #[serde(flatten)]
+    collection: Collection,
+    quotes: Vec<Quote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCollection {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    is_public: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCollection {
+    name: Option<String>,
+    description: Option<String>,
+    is_public: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionQuery {
+    #[serde(default)]
+    mine: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddQuote {
+    quote_id: String,
+    /// Zero-based slot to insert at; appends when omitted.
+    position: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderQuotes {
+    quote_ids: Vec<String>,
+}
+
+pub async fn create_tables(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS collections (
+            id VARCHAR(36) PRIMARY KEY,
+            slug VARCHAR(80) NOT NULL UNIQUE,
+            name VARCHAR(100) NOT NULL,
+            description TEXT,
+            is_public BOOLEAN NOT NULL DEFAULT FALSE,
+            owner CHAR(64) NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            INDEX idx_collections_owner (owner)
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS collection_quotes (
+            collection_id VARCHAR(36) NOT NULL,
+            quote_id VARCHAR(36) NOT NULL,
+            position INT UNSIGNED NOT NULL,
+            added_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (collection_id, quote_id),
+            INDEX idx_collection_quotes_position (collection_id, position)
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+/// Collections need a stable owner, so unlike likes they are not keyed on
+/// the caller's address: without an `X-Client-Id` the caller owns nothing.
+fn owner_key(headers: &HeaderMap) -> Option<String> {
+    client_id(headers).map(|id| hex::encode(Sha256::digest(format!("client:{}", id).as_bytes())))
+}
+
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_end_matches('-').chars().take(60).collect();
+    let suffix = &Uuid::new_v4().simple().to_string()[..8];
+
+    if slug.is_empty() {
+        suffix.to_string()
+    } else {
+        format!("{}-{}", slug.trim_end_matches('-'), suffix)
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, StatusCode> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || contains_inappropriate_content(name) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(name.to_string())
+}
+
+fn db_error(e: sqlx::Error) -> StatusCode {
+    eprintln!("Database error: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+const COLLECTION_COLUMNS: &str =
+    "id, slug, name, description, is_public, owner, created_at, updated_at,
+     (SELECT COUNT(*) FROM collection_quotes
+      JOIN quotes ON quotes.id = collection_quotes.quote_id
+      WHERE collection_quotes.collection_id = collections.id AND quotes.deleted_at IS NULL) AS quote_count";
+
+fn collection_from_row(row: &MySqlRow, owner: Option<&str>) -> Collection {
+    let row_owner: String = row.get("owner");
+
+    Collection {
+        id: row.get("id"),
+        slug: row.get("slug"),
+        name: row.get("name"),
+        description: row.get("description"),
+        is_public: row.get("is_public"),
+        quote_count: row.get("quote_count"),
+        owned: owner == Some(row_owner.as_str()),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Loads a collection the caller may see. Private collections of other
+/// clients are reported as missing rather than forbidden.
+async fn visible_collection(
+    pool: &MySqlPool,
+    slug: &str,
+    owner: Option<&str>
+) -> Result<Collection, StatusCode> {
+    let row = sqlx
+        ::query(&format!("SELECT {} FROM collections WHERE slug = ?", COLLECTION_COLUMNS))
+        .bind(slug)
+        .fetch_optional(pool).await
+        .map_err(db_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let collection = collection_from_row(&row, owner);
+    if collection.is_public || collection.owned {
+        Ok(collection)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+async fn owned_collection(
+    pool: &MySqlPool,
+    slug: &str,
+    headers: &HeaderMap
+) -> Result<Collection, StatusCode> {
+    let owner = owner_key(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let collection = visible_collection(pool, slug, Some(&owner)).await?;
+    if collection.owned {
+        Ok(collection)
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+async fn collection_quotes(pool: &MySqlPool, collection_id: &str) -> Result<Vec<Quote>, sqlx::Error> {
+    let rows = sqlx
+        ::query(
+            &format!(
+                "SELECT {} FROM quotes
+                 JOIN collection_quotes ON collection_quotes.quote_id = quotes.id
+                 WHERE collection_quotes.collection_id = ? AND quotes.deleted_at IS NULL
+                 ORDER BY collection_quotes.position",
+                QUOTE_COLUMNS
+            )
+        )
+        .bind(collection_id)
+        .fetch_all(pool).await?;
+
+    Ok(rows.iter().map(quote_from_row).collect())
+}
+
+/// Rewrites positions as 0..n in the given order, closing any gaps.
+async fn write_positions(
+    tx: &mut Transaction<'_, MySql>,
+    collection_id: &str,
+    quote_ids: &[String]
+) -> Result<(), sqlx::Error> {
+    for (position, quote_id) in quote_ids.iter().enumerate() {
+        sqlx::query(
+            "UPDATE collection_quotes SET position = ? WHERE collection_id = ? AND quote_id = ?"
+        )
+            .bind(position as u32)
+            .bind(collection_id)
+            .bind(quote_id)
+            .execute(&mut **tx).await?;
+    }
+    Ok(())
+}
+
+async fn member_ids(
+    tx: &mut Transaction<'_, MySql>,
+    collection_id: &str
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT quote_id FROM collection_quotes WHERE collection_id = ? ORDER BY position FOR UPDATE"
+    )
+        .bind(collection_id)
+        .fetch_all(&mut **tx).await
+}
+
+/// Members whose quote is in the trash, which `collection_quotes` leaves out.
+async fn trashed_member_ids(
+    tx: &mut Transaction<'_, MySql>,
+    collection_id: &str
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT collection_quotes.quote_id FROM collection_quotes
+         JOIN quotes ON quotes.id = collection_quotes.quote_id
+         WHERE collection_quotes.collection_id = ? AND quotes.deleted_at IS NOT NULL"
+    )
+        .bind(collection_id)
+        .fetch_all(&mut **tx).await
+}
+
+async fn detail(pool: &MySqlPool, slug: &str, owner: Option<&str>) -> Result<Json<CollectionDetail>, StatusCode> {
+    let collection = visible_collection(pool, slug, owner).await?;
+    let quotes = collection_quotes(pool, &collection.id).await.map_err(db_error)?;
+    Ok(Json(CollectionDetail { collection, quotes }))
+}
+
+pub async fn list_collections(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CollectionQuery>
+) -> Result<Json<Vec<Collection>>, StatusCode> {
+    let owner = owner_key(&headers);
+
+    let rows = if params.mine {
+        let Some(owner) = &owner else {
+            return Ok(Json(Vec::new()));
+        };
+        sqlx
+            ::query(
+                &format!("SELECT {} FROM collections WHERE owner = ? ORDER BY updated_at DESC", COLLECTION_COLUMNS)
+            )
+            .bind(owner)
+            .fetch_all(&state.pool).await
+    } else {
+        sqlx
+            ::query(
+                &format!(
+                    "SELECT {} FROM collections WHERE is_public = TRUE OR owner = ? ORDER BY updated_at DESC",
+                    COLLECTION_COLUMNS
+                )
+            )
+            .bind(owner.as_deref().unwrap_or_default())
+            .fetch_all(&state.pool).await
+    };
+
+    match rows {
+        Ok(rows) => {
+            Ok(
+                Json(
+                    rows
+                        .iter()
+                        .map(|row| collection_from_row(row, owner.as_deref()))
+                        .collect()
+                )
+            )
+        }
+        Err(e) => Err(db_error(e)),
+    }
+}
+
+pub async fn create_collection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateCollection>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    let owner = owner_key(&headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    let name = validate_name(&payload.name)?;
+    if payload.description.as_deref().is_some_and(contains_inappropriate_content) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let slug = slugify(&name);
+    sqlx::query(
+        "INSERT INTO collections (id, slug, name, description, is_public, owner) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&slug)
+        .bind(&name)
+        .bind(&payload.description)
+        .bind(payload.is_public)
+        .bind(&owner)
+        .execute(&state.pool).await
+        .map_err(db_error)?;
+
+    detail(&state.pool, &slug, Some(&owner)).await
+}
+
+pub async fn get_collection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    detail(&state.pool, &slug, owner_key(&headers).as_deref()).await
+}
+
+pub async fn update_collection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Json(payload): Json<UpdateCollection>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    let collection = owned_collection(&state.pool, &slug, &headers).await?;
+    let name = payload.name.as_deref().map(validate_name).transpose()?;
+    if payload.description.as_deref().is_some_and(contains_inappropriate_content) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    sqlx::query(
+        "UPDATE collections SET
+             name = COALESCE(?, name),
+             description = COALESCE(?, description),
+             is_public = COALESCE(?, is_public)
+         WHERE id = ?"
+    )
+        .bind(name)
+        .bind(&payload.description)
+        .bind(payload.is_public)
+        .bind(&collection.id)
+        .execute(&state.pool).await
+        .map_err(db_error)?;
+
+    detail(&state.pool, &slug, owner_key(&headers).as_deref()).await
+}
+
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>
+) -> Result<StatusCode, StatusCode> {
+    let collection = owned_collection(&state.pool, &slug, &headers).await?;
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+        sqlx::query("DELETE FROM collection_quotes WHERE collection_id = ?")
+            .bind(&collection.id)
+            .execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM collections WHERE id = ?")
+            .bind(&collection.id)
+            .execute(&mut *tx).await?;
+        tx.commit().await
+    }.await;
+
+    result.map(|_| StatusCode::NO_CONTENT).map_err(db_error)
+}
+
+pub async fn add_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Json(payload): Json<AddQuote>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    let collection = owned_collection(&state.pool, &slug, &headers).await?;
+
+    let exists: Option<String> = sqlx
+        ::query_scalar("SELECT id FROM quotes WHERE id = ? AND deleted_at IS NULL")
+        .bind(&payload.quote_id)
+        .fetch_optional(&state.pool).await
+        .map_err(db_error)?;
+    if exists.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+        let mut ids = member_ids(&mut tx, &collection.id).await?;
+
+        if ids.contains(&payload.quote_id) {
+            // Re-adding only moves the quote.
+            ids.retain(|id| id != &payload.quote_id);
+        } else {
+            sqlx::query(
+                "INSERT INTO collection_quotes (collection_id, quote_id, position) VALUES (?, ?, ?)"
+            )
+                .bind(&collection.id)
+                .bind(&payload.quote_id)
+                .bind(ids.len() as u32)
+                .execute(&mut *tx).await?;
+        }
+
+        let position = payload.position.map_or(ids.len(), |p| (p as usize).min(ids.len()));
+        ids.insert(position, payload.quote_id.clone());
+        write_positions(&mut tx, &collection.id, &ids).await?;
+
+        sqlx::query("UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(&collection.id)
+            .execute(&mut *tx).await?;
+        tx.commit().await
+    }.await;
+
+    result.map_err(db_error)?;
+    detail(&state.pool, &slug, owner_key(&headers).as_deref()).await
+}
+
+pub async fn remove_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((slug, quote_id)): Path<(String, String)>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    let collection = owned_collection(&state.pool, &slug, &headers).await?;
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+        let removed = sqlx
+            ::query("DELETE FROM collection_quotes WHERE collection_id = ? AND quote_id = ?")
+            .bind(&collection.id)
+            .bind(&quote_id)
+            .execute(&mut *tx).await?
+            .rows_affected();
+
+        if removed > 0 {
+            let ids = member_ids(&mut tx, &collection.id).await?;
+            write_positions(&mut tx, &collection.id, &ids).await?;
+            sqlx::query("UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(&collection.id)
+                .execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(removed)
+    }.await;
+
+    match result {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => detail(&state.pool, &slug, owner_key(&headers).as_deref()).await,
+        Err(e) => Err(db_error(e)),
+    }
+}
+
+pub async fn reorder_quotes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Json(payload): Json<ReorderQuotes>
+) -> Result<Json<CollectionDetail>, StatusCode> {
+    let collection = owned_collection(&state.pool, &slug, &headers).await?;
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+        let members = member_ids(&mut tx, &collection.id).await?;
+        let trashed = trashed_member_ids(&mut tx, &collection.id).await?;
+        let (trashed, mut current): (Vec<String>, Vec<String>) = members
+            .into_iter()
+            .partition(|id| trashed.contains(id));
+        let mut requested = payload.quote_ids.clone();
+        current.sort();
+        requested.sort();
+
+        // The new order has to be a permutation of the members clients can
+        // see. Those in the trash go after them, ready for a restore.
+        if current != requested {
+            return Ok(false);
+        }
+
+        let order: Vec<String> = payload.quote_ids.iter().cloned().chain(trashed).collect();
+        write_positions(&mut tx, &collection.id, &order).await?;
+        sqlx::query("UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(&collection.id)
+            .execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(true)
+    }.await;
+
+    match result {
+        Ok(true) => detail(&state.pool, &slug, owner_key(&headers).as_deref()).await,
+        Ok(false) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => Err(db_error(e)),
+    }
+}
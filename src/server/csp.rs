@@ -0,0 +1,47 @@
+//! Content-Security-Policy for the documents the server renders.
+//!
+//! Pages start from `default-src 'none'` and allow only what they use.
+//! Their inline `<style>` and `<script>` blocks are allowed by hash rather
+//! than `'unsafe-inline'`, so markup that slips into a quote or author can
+//! never run or restyle the page even if escaping were missed somewhere.
+
+use axum::http::header::{ HeaderName, CONTENT_SECURITY_POLICY };
+use base64::{ engine::general_purpose::STANDARD, Engine };
+use sha2::{ Digest, Sha256 };
+
+const BASE: &str = "default-src 'none'; base-uri 'none'; form-action 'none'";
+
+/// The `'sha256-…'` source for an inline block, hashed exactly as it appears
+/// between its tags.
+pub fn inline_source(content: &str) -> String {
+    format!("'sha256-{}'", STANDARD.encode(Sha256::digest(content.as_bytes())))
+}
+
+/// For documents that may load nothing at all, such as SVG cards opened
+/// directly.
+pub fn locked() -> (HeaderName, String) {
+    (CONTENT_SECURITY_POLICY, BASE.to_string())
+}
+
+/// A page with one inline stylesheet and images from `img_origin`, which
+/// no other site may frame.
+pub fn page(style: &str, img_origin: &str) -> (HeaderName, String) {
+    (
+        CONTENT_SECURITY_POLICY,
+        format!(
+            "{}; style-src {}; img-src {}; frame-ancestors 'none'",
+            BASE,
+            inline_source(style),
+            img_origin
+        ),
+    )
+}
+
+/// A page meant to be framed by any site, with one inline stylesheet and
+/// one inline script.
+pub fn embeddable(style: &str, script: &str) -> (HeaderName, String) {
+    (
+        CONTENT_SECURITY_POLICY,
+        format!("{}; style-src {}; script-src {}; frame-ancestors *", BASE, inline_source(style), inline_source(script)),
+    )
+}
@@ -0,0 +1,273 @@
+//! Quote of the day.
+//!
+//! The pick for a date is seeded from the date itself, skips anything shown
+//! within the last `DAILY_NO_REPEAT_DAYS` days and is persisted in
+//! `daily_quotes`, so every visitor sees the same quote until midnight in
+//! `DAILY_TIMEZONE`. Admins can pin a specific quote to any date.
+
+use axum::{ extract::{ Path, State }, http::{ HeaderMap, StatusCode }, response::Json };
+use chrono::{ DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc };
+use chrono_tz::Tz;
+use serde::Deserialize;
+use sqlx::{ mysql::MySqlPool, Row };
+use std::{ collections::HashSet, env };
+use tokio::sync::RwLock;
+
+use crate::{ quote_from_row, require_admin, AppState, Quote, QUOTE_COLUMNS };
+
+pub struct DailyState {
+    timezone: Tz,
+    no_repeat_days: i64,
+    cached: RwLock<Option<(NaiveDate, Quote)>>,
+}
+
+impl DailyState {
+    pub fn from_env() -> DailyState {
+        let timezone = env
+            ::var("DAILY_TIMEZONE")
+            .ok()
+            .and_then(|tz| tz.parse::<Tz>().ok())
+            .unwrap_or(Tz::UTC);
+        let no_repeat_days = env
+            ::var("DAILY_NO_REPEAT_DAYS")
+            .ok()
+            .and_then(|days| days.parse().ok())
+            .unwrap_or(30);
+
+        DailyState {
+            timezone,
+            no_repeat_days,
+            cached: RwLock::new(None),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Midnight of `day` in the daily timezone.
+    pub fn starts_at(&self, day: NaiveDate) -> DateTime<Utc> {
+        self.timezone
+            .from_local_datetime(&day.and_time(NaiveTime::MIN))
+            .earliest()
+            // Midnight can fall into a DST gap; the day then starts an hour
+            // later, which UTC midnight is close enough to for a timestamp.
+            .map_or_else(|| day.and_time(NaiveTime::MIN).and_utc(), |start| start.with_timezone(&Utc))
+    }
+
+    pub async fn invalidate(&self, day: NaiveDate) {
+        let mut cached = self.cached.write().await;
+        if cached.as_ref().is_some_and(|(cached_day, _)| *cached_day == day) {
+            *cached = None;
+        }
+    }
+
+    /// Today's quote, picking and persisting it on first use.
+    pub async fn current(&self, pool: &MySqlPool) -> Result<Option<Quote>, sqlx::Error> {
+        let day = self.today();
+
+        if let Some((cached_day, quote)) = &*self.cached.read().await {
+            if *cached_day == day {
+                return Ok(Some(quote.clone()));
+            }
+        }
+
+        let quote = resolve_daily_quote(pool, day, self.no_repeat_days).await?;
+        if let Some(quote) = &quote {
+            *self.cached.write().await = Some((day, quote.clone()));
+        }
+        Ok(quote)
+    }
+
+    /// Drops the cached pick if it is `quote_id`, e.g. after it was deleted.
+    pub async fn forget_quote(&self, quote_id: &str) {
+        let mut cached = self.cached.write().await;
+        if cached.as_ref().is_some_and(|(_, quote)| quote.id == quote_id) {
+            *cached = None;
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinDailyQuote {
+    quote_id: String,
+}
+
+pub async fn create_table(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS daily_quotes (
+            day DATE PRIMARY KEY,
+            quote_id VARCHAR(36) NOT NULL,
+            pinned BOOLEAN NOT NULL DEFAULT FALSE,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+/// SplitMix64 over the day number, so the same date always maps to the same
+/// position in the candidate list regardless of host or restart.
+fn day_seed(day: NaiveDate) -> u64 {
+    let mut z = (day.num_days_from_ce() as u64).wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+async fn fetch_assigned(pool: &MySqlPool, day: NaiveDate) -> Result<Option<Quote>, sqlx::Error> {
+    let row = sqlx
+        ::query(
+            &format!(
+                "SELECT {} FROM quotes
+                 WHERE id = (SELECT quote_id FROM daily_quotes WHERE day = ?) AND deleted_at IS NULL",
+                QUOTE_COLUMNS
+            )
+        )
+        .bind(day)
+        .fetch_optional(pool).await?;
+
+    Ok(row.as_ref().map(quote_from_row))
+}
+
+async fn resolve_daily_quote(
+    pool: &MySqlPool,
+    day: NaiveDate,
+    no_repeat_days: i64
+) -> Result<Option<Quote>, sqlx::Error> {
+    if let Some(quote) = fetch_assigned(pool, day).await? {
+        return Ok(Some(quote));
+    }
+    let pinned: Option<bool> = sqlx
+        ::query_scalar("SELECT pinned FROM daily_quotes WHERE day = ?")
+        .bind(day)
+        .fetch_optional(pool).await?;
+
+    let ids: Vec<String> = sqlx
+        ::query("SELECT id FROM quotes WHERE deleted_at IS NULL AND translation_of IS NULL ORDER BY created_at, id")
+        .fetch_all(pool).await?
+        .iter()
+        .map(|row| row.get("id"))
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(None);
+    }
+
+    let recent: HashSet<String> = sqlx
+        ::query("SELECT quote_id FROM daily_quotes WHERE day >= ? AND day < ?")
+        .bind(day - Duration::days(no_repeat_days))
+        .bind(day)
+        .fetch_all(pool).await?
+        .iter()
+        .map(|row| row.get("quote_id"))
+        .collect();
+
+    let fresh: Vec<&String> = ids
+        .iter()
+        .filter(|id| !recent.contains(*id))
+        .collect();
+    // A collection smaller than the window has to repeat eventually; fall
+    // back to the full list rather than showing nothing.
+    let candidates = if fresh.is_empty() { ids.iter().collect() } else { fresh };
+    let pick = candidates[(day_seed(day) % (candidates.len() as u64)) as usize];
+
+    // A pinned quote that is in the trash is stood in for, but the pin is
+    // left alone so that the quote is back for its day if it is restored.
+    if pinned == Some(true) {
+        let row = sqlx
+            ::query(&format!("SELECT {} FROM quotes WHERE id = ?", QUOTE_COLUMNS))
+            .bind(pick)
+            .fetch_optional(pool).await?;
+        return Ok(row.as_ref().map(quote_from_row));
+    }
+
+    // A stale unpinned row (its quote was removed) is replaced; a pin is kept.
+    sqlx::query(
+        "INSERT INTO daily_quotes (day, quote_id, pinned) VALUES (?, ?, FALSE)
+         ON DUPLICATE KEY UPDATE quote_id = IF(pinned, quote_id, VALUES(quote_id))"
+    )
+        .bind(day)
+        .bind(pick)
+        .execute(pool).await?;
+
+    fetch_assigned(pool, day).await
+}
+
+pub async fn get_daily_quote(State(state): State<AppState>) -> Result<Json<Quote>, StatusCode> {
+    match state.daily.current(&state.pool).await {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn pin_daily_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(day): Path<NaiveDate>,
+    Json(payload): Json<PinDailyQuote>
+) -> Result<Json<Quote>, StatusCode> {
+    require_admin(&headers)?;
+
+    let quote = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL", QUOTE_COLUMNS))
+        .bind(&payload.quote_id)
+        .fetch_optional(&state.pool).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(|row| quote_from_row(&row))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let result = sqlx
+        ::query(
+            "INSERT INTO daily_quotes (day, quote_id, pinned) VALUES (?, ?, TRUE)
+             ON DUPLICATE KEY UPDATE quote_id = VALUES(quote_id), pinned = TRUE"
+        )
+        .bind(day)
+        .bind(&quote.id)
+        .execute(&state.pool).await;
+
+    match result {
+        Ok(_) => {
+            state.daily.invalidate(day).await;
+            Ok(Json(quote))
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn unpin_daily_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(day): Path<NaiveDate>
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&headers)?;
+
+    let result = sqlx
+        ::query("DELETE FROM daily_quotes WHERE day = ?")
+        .bind(day)
+        .execute(&state.pool).await;
+
+    match result {
+        Ok(_) => {
+            state.daily.invalidate(day).await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Quotes embedded on other sites.
+//!
+//! `/embed` is a self-contained page meant for an iframe: a random, daily or
+//! specific quote in a dark or light theme, optionally filtered by tag or
+//! author and shown in a preferred language when a translation exists.
+//! `/embed.js` turns placeholder elements on a host page into such iframes
+//! and sizes them to their content. `/oembed` describes share links
+//! (`/share/:id`) for platforms that unfurl links through oEmbed.
+
+use axum::{
+    extract::{ Query, State },
+    http::{ header::{ CACHE_CONTROL, CONTENT_TYPE }, HeaderMap, StatusCode },
+    response::{ Html, IntoResponse, Json, Response },
+};
+use reqwest::Url;
+use serde::{ Deserialize, Serialize };
+
+use crate::{
+    cards::{ share_url, CardSize },
+    csp,
+    escape_html,
+    fetch_quote,
+    language,
+    public_url,
+    quote_from_row,
+    AppState,
+    Quote,
+    QUOTE_COLUMNS,
+};
+
+const EMBED_SCRIPT: &str = include_str!("../../assets/embed.js");
+/// Tells the embedding page how tall the quote is; see `assets/embed.js`.
+const RESIZE_SCRIPT: &str =
+    "(function () { function report() { parent.postMessage({ type: 'rusted-quotes:resize', height: document.documentElement.scrollHeight }, '*'); } window.addEventListener('load', report); window.addEventListener('resize', report); })();";
+const MIN_REFRESH_SECONDS: u32 = 30;
+const OEMBED_WIDTH: u32 = 560;
+const OEMBED_HEIGHT: u32 = 200;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbedTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+struct Palette {
+    background: &'static str,
+    border: &'static str,
+    text: &'static str,
+    author: &'static str,
+    link: &'static str,
+}
+
+impl EmbedTheme {
+    fn palette(self) -> Palette {
+        match self {
+            EmbedTheme::Dark => Palette {
+                background: "#0f172a",
+                border: "rgba(0, 255, 255, 0.3)",
+                text: "#f1f5f9",
+                author: "#ff0080",
+                link: "#00ffff",
+            },
+            EmbedTheme::Light => Palette {
+                background: "#ffffff",
+                border: "#e2e8f0",
+                text: "#0f172a",
+                author: "#db2777",
+                link: "#0891b2",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbedQuery {
+    /// `random` (the default), `daily`, or a quote id.
+    quote: Option<String>,
+    #[serde(default)]
+    theme: EmbedTheme,
+    /// Random quotes only.
+    tag: Option<String>,
+    /// Random quotes only.
+    author: Option<String>,
+    /// Preferred languages as for `GET /api/quotes`, including `auto`.
+    lang: Option<String>,
+    /// Reloads the frame every N seconds, for random and daily quotes.
+    refresh: Option<u32>,
+}
+
+/// The translation of `quote` in the first preferred language that has one,
+/// or `quote` itself.
+async fn localize(state: &AppState, quote: Quote, languages: &[String]) -> Result<Quote, sqlx::Error> {
+    if languages.is_empty() {
+        return Ok(quote);
+    }
+
+    let root = quote.translation_of.clone().unwrap_or_else(|| quote.id.clone());
+    for language in languages {
+        let sql = format!(
+            "SELECT {} FROM quotes
+             WHERE (id = ? OR translation_of = ?) AND deleted_at IS NULL AND {}
+             ORDER BY translation_of IS NOT NULL
+             LIMIT 1",
+            QUOTE_COLUMNS,
+            language::language_filter(1)
+        );
+        let row = sqlx
+            ::query(&sql)
+            .bind(&root)
+            .bind(&root)
+            .bind(language)
+            .bind(language)
+            .fetch_optional(&state.pool).await?;
+        if let Some(row) = row {
+            return Ok(quote_from_row(&row));
+        }
+    }
+    Ok(quote)
+}
+
+pub async fn embed_page(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<EmbedQuery>
+) -> Result<Response, StatusCode> {
+    let languages = language::requested_languages(params.lang.as_deref(), &headers)?;
+    let selection = params.quote.as_deref().map(str::trim).unwrap_or("random");
+
+    let result = async {
+        let quote = match selection {
+            "random" | "" => state.random.pick(&state.pool, params.author.as_deref(), params.tag.as_deref()).await?,
+            "daily" => state.daily.current(&state.pool).await?,
+            id => fetch_quote(&state.pool, id).await?,
+        };
+        match quote {
+            Some(quote) => localize(&state, quote, &languages).await.map(Some),
+            None => Ok(None),
+        }
+    }.await;
+
+    let quote = match result {
+        Ok(Some(quote)) => quote,
+        Ok(None) => {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let (cache_control, refreshes) = match selection {
+        "random" | "" => ("no-store".to_string(), true),
+        "daily" => ("public, max-age=300".to_string(), true),
+        _ => ("public, max-age=3600".to_string(), false),
+    };
+    let refresh = params.refresh
+        .filter(|_| refreshes)
+        .map(|seconds| format!(r#"<meta http-equiv="refresh" content="{}">"#, seconds.max(MIN_REFRESH_SECONDS)))
+        .unwrap_or_default();
+    let author = match &quote.author {
+        Some(author) => format!("<cite>— {}</cite>", escape_html(author)),
+        None => "<cite></cite>".to_string(),
+    };
+    let palette = params.theme.palette();
+    let style = format!(
+        r#"
+html, body {{ margin: 0; background: transparent; }}
+figure {{ box-sizing: border-box; margin: 0; padding: 20px 24px; border-radius: 12px; background: {background}; border: 1px solid {border}; color: {text}; font-family: 'JetBrains Mono', Consolas, monospace; }}
+blockquote {{ margin: 0; font-size: 17px; line-height: 1.5; white-space: pre-line; overflow-wrap: anywhere; }}
+figcaption {{ margin-top: 12px; display: flex; justify-content: space-between; align-items: baseline; gap: 12px; font-size: 13px; }}
+cite {{ color: {author_color}; font-style: normal; font-weight: bold; }}
+a {{ color: {link}; text-decoration: none; white-space: nowrap; }}
+a:hover {{ text-decoration: underline; }}
+"#,
+        background = palette.background,
+        border = palette.border,
+        text = palette.text,
+        author_color = palette.author,
+        link = palette.link
+    );
+
+    let page = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="UTF-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+{refresh}
+<title>Quote | Rusted Quotes</title>
+<style>{style}</style>
+</head>
+<body>
+<figure>
+<blockquote>{quote}</blockquote>
+<figcaption>{author}<a href="{share}" target="_blank" rel="noopener">Rusted Quotes</a></figcaption>
+</figure>
+<script>{script}</script>
+</body>
+</html>
+"#,
+        lang = escape_html(quote.language.as_deref().unwrap_or("en")),
+        refresh = refresh,
+        style = style,
+        quote = escape_html(&quote.quote),
+        author = author,
+        share = escape_html(&share_url(&quote.id)),
+        script = RESIZE_SCRIPT
+    );
+
+    Ok(
+        (
+            [(CACHE_CONTROL, cache_control), csp::embeddable(&style, RESIZE_SCRIPT)],
+            Html(page),
+        ).into_response()
+    )
+}
+
+pub async fn embed_script() -> impl IntoResponse {
+    let origin = serde_json::to_string(&public_url()).expect("strings always serialize");
+    (
+        [
+            (CONTENT_TYPE, "application/javascript; charset=utf-8"),
+            (CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        EMBED_SCRIPT.replace("__ORIGIN__", &origin),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OEmbedQuery {
+    url: String,
+    maxwidth: Option<u32>,
+    maxheight: Option<u32>,
+    format: Option<String>,
+}
+
+/// A `rich` oEmbed response, see <https://oembed.com/#section2.3>.
+#[derive(Debug, Serialize)]
+pub struct OEmbed {
+    version: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author_name: Option<String>,
+    provider_name: &'static str,
+    provider_url: String,
+    cache_age: u32,
+    html: String,
+    width: u32,
+    height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_height: Option<u32>,
+}
+
+/// The quote id in a link to this server: `/share/:id`, `/api/quotes/:id`
+/// or `/embed?quote=:id`.
+fn quote_id_from_url(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    let own = Url::parse(&public_url()).ok()?;
+    if url.origin() != own.origin() {
+        return None;
+    }
+
+    let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+    match segments.as_slice() {
+        ["share", id] | ["api", "quotes", id] => Some(id.to_string()),
+        ["embed"] => url
+            .query_pairs()
+            .find(|(key, _)| key == "quote")
+            .map(|(_, id)| id.into_owned())
+            .filter(|id| id != "random" && id != "daily"),
+        _ => None,
+    }
+}
+
+/// Only JSON is offered; the spec asks for 501 on other formats.
+pub async fn oembed(
+    State(state): State<AppState>,
+    Query(params): Query<OEmbedQuery>
+) -> Result<Json<OEmbed>, StatusCode> {
+    if params.format.as_deref().is_some_and(|format| format != "json") {
+        return Err(StatusCode::NOT_IMPLEMENTED);
+    }
+    let id = quote_id_from_url(&params.url).ok_or(StatusCode::NOT_FOUND)?;
+
+    let quote = fetch_quote(&state.pool, &id).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let width = params.maxwidth.map_or(OEMBED_WIDTH, |max| max.min(OEMBED_WIDTH));
+    let height = params.maxheight.map_or(OEMBED_HEIGHT, |max| max.min(OEMBED_HEIGHT));
+    let title = match &quote.author {
+        Some(author) => format!("Quote by {}", author),
+        None => "Quote".to_string(),
+    };
+    let embed_url = format!("{}/embed?quote={}", public_url(), quote.id);
+    let html = format!(
+        r#"<iframe src="{}" width="{}" height="{}" style="border:0" title="{}" loading="lazy"></iframe>"#,
+        escape_html(&embed_url),
+        width,
+        height,
+        escape_html(&title)
+    );
+
+    // The thumbnail may only be offered if it fits the requested bounds.
+    let (thumbnail_width, thumbnail_height) = CardSize::Og.dimensions();
+    let thumbnail_fits =
+        params.maxwidth.is_none_or(|max| max >= thumbnail_width) &&
+        params.maxheight.is_none_or(|max| max >= thumbnail_height);
+    let thumbnail = thumbnail_fits.then(|| format!("{}/api/quotes/{}/card.png", public_url(), quote.id));
+
+    Ok(
+        Json(OEmbed {
+            version: "1.0",
+            kind: "rich",
+            title,
+            author_name: quote.author.clone(),
+            provider_name: "Rusted Quotes",
+            provider_url: public_url(),
+            cache_age: 3600,
+            html,
+            width,
+            height,
+            thumbnail_width: thumbnail.as_ref().map(|_| thumbnail_width),
+            thumbnail_height: thumbnail.as_ref().map(|_| thumbnail_height),
+            thumbnail_url: thumbnail,
+        })
+    )
+}
+
+/// The discovery link for a share page, per <https://oembed.com/#section4>.
+pub fn discovery_url(share_url: &str) -> String {
+    Url::parse_with_params(&format!("{}/oembed", public_url()), &[("url", share_url), ("format", "json")])
+        .map(String::from)
+        .unwrap_or_default()
+}
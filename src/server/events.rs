@@ -0,0 +1,188 @@
+//! Live quote updates over Server-Sent Events.
+//!
+//! Handlers publish a [`QuoteEvent`] whenever a quote is created, edited or
+//! deleted. Each event gets an increasing id and is kept in a short backlog,
+//! so a client that reconnects with `Last-Event-ID` receives what it missed.
+//! When the gap is too old for the backlog, or the client fell behind the
+//! channel, it gets a `resync` event and should reload instead.
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{ sse::{ Event, KeepAlive, Sse }, IntoResponse },
+};
+use chrono::Utc;
+use futures_util::stream::{ self, Stream, StreamExt };
+use serde::Serialize;
+use std::{ collections::VecDeque, convert::Infallible, sync::{ Arc, Mutex } };
+use tokio::sync::broadcast::{ self, error::RecvError };
+
+use crate::{ AppState, Quote };
+
+const CHANNEL_CAPACITY: usize = 256;
+const BACKLOG_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuoteEvent {
+    Created {
+        quote: Quote,
+    },
+    Updated {
+        quote: Quote,
+    },
+    /// An admin verified the quote's source.
+    Approved {
+        quote: Quote,
+    },
+    Deleted {
+        id: String,
+    },
+}
+
+impl QuoteEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            QuoteEvent::Created { .. } => "created",
+            QuoteEvent::Updated { .. } => "updated",
+            QuoteEvent::Approved { .. } => "approved",
+            QuoteEvent::Deleted { .. } => "deleted",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StoredEvent {
+    pub id: u64,
+    pub event: QuoteEvent,
+}
+
+struct Backlog {
+    next_id: u64,
+    events: VecDeque<Arc<StoredEvent>>,
+}
+
+pub struct EventHub {
+    sender: broadcast::Sender<Arc<StoredEvent>>,
+    backlog: Mutex<Backlog>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        EventHub {
+            sender,
+            backlog: Mutex::new(Backlog {
+                // Ids continue to grow across restarts, so an id from before
+                // a restart is recognised as too old rather than replayed.
+                next_id: Utc::now().timestamp_millis().max(0) as u64 * 1000,
+                events: VecDeque::new(),
+            }),
+        }
+    }
+}
+
+impl EventHub {
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<StoredEvent>> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: QuoteEvent) {
+        let mut backlog = self.backlog.lock().unwrap();
+        let stored = Arc::new(StoredEvent { id: backlog.next_id, event });
+        backlog.next_id += 1;
+        if backlog.events.len() == BACKLOG_SIZE {
+            backlog.events.pop_front();
+        }
+        backlog.events.push_back(stored.clone());
+
+        // Sending under the lock keeps channel order equal to id order. An
+        // error only means nobody is listening.
+        let _ = self.sender.send(stored);
+    }
+
+    /// Subscribes and collects the events after `last_id`. `None` means the
+    /// client missed more than the backlog holds, or sent an id this server
+    /// never issued (e.g. from before a restart).
+    fn resume(
+        &self,
+        last_id: Option<u64>
+    ) -> (broadcast::Receiver<Arc<StoredEvent>>, Option<Vec<Arc<StoredEvent>>>) {
+        let backlog = self.backlog.lock().unwrap();
+        let receiver = self.sender.subscribe();
+
+        let Some(last_id) = last_id else {
+            return (receiver, Some(Vec::new()));
+        };
+        let oldest = backlog.events.front().map_or(backlog.next_id, |event| event.id);
+        if last_id.saturating_add(1) < oldest || last_id >= backlog.next_id {
+            return (receiver, None);
+        }
+
+        let missed = backlog.events
+            .iter()
+            .filter(|event| event.id > last_id)
+            .cloned()
+            .collect();
+        (receiver, Some(missed))
+    }
+}
+
+fn to_sse(stored: &StoredEvent) -> Event {
+    Event::default()
+        .id(stored.id.to_string())
+        .event(stored.event.name())
+        .json_data(&stored.event)
+        .unwrap_or_else(|_| Event::default().event("resync").data("{}"))
+}
+
+fn resync() -> Event {
+    Event::default().event("resync").data("{}")
+}
+
+fn live_events(
+    receiver: broadcast::Receiver<Arc<StoredEvent>>,
+    last_sent: u64
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((receiver, last_sent), |(mut receiver, last_sent)| async move {
+        loop {
+            match receiver.recv().await {
+                // Already sent as part of the replay.
+                Ok(stored) if stored.id <= last_sent => {
+                    continue;
+                }
+                Ok(stored) => {
+                    let event = to_sse(&stored);
+                    return Some((Ok(event), (receiver, stored.id)));
+                }
+                Err(RecvError::Lagged(_)) => {
+                    return Some((Ok(resync()), (receiver, last_sent)));
+                }
+                Err(RecvError::Closed) => {
+                    return None;
+                }
+            }
+        }
+    })
+}
+
+pub async fn stream_quotes(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    let last_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    let (receiver, missed) = state.events.resume(last_id);
+    let (replay, last_sent) = match missed {
+        Some(missed) => {
+            let last_sent = missed.last().map_or(last_id.unwrap_or(0), |event| event.id);
+            (missed.iter().map(|stored| Ok(to_sse(stored))).collect(), last_sent)
+        }
+        None => (vec![Ok(resync())], 0),
+    };
+
+    let stream = stream::iter(replay).chain(live_events(receiver, last_sent));
+
+    // Keeps reverse proxies from buffering the stream.
+    ([("x-accel-buffering", "no")], Sse::new(stream).keep_alive(KeepAlive::default()))
+}
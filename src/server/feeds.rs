@@ -0,0 +1,297 @@
+//! RSS and Atom feeds.
+//!
+//! `/feeds/latest.{rss,atom}` runs the same query as `GET /api/quotes`
+//! (newest first) and accepts its `tag` and `author` filters.
+//! `/feeds/daily.{rss,atom}` lists the quote of the day for recent days,
+//! with the same filters. Entry ids are derived from the quote id, so an
+//! edit updates an entry instead of adding a new one; in the daily feed the
+//! day is part of the id, because a quote can be picked again later.
+
+use atom_syndication as atom;
+use axum::{
+    extract::{ Query, State },
+    http::{ header::CONTENT_TYPE, StatusCode, Uri },
+    response::IntoResponse,
+};
+use chrono::{ DateTime, NaiveDate, Utc };
+use rss::extension::dublincore::{ self, DublinCoreExtension };
+use serde::Deserialize;
+use sqlx::Row;
+use std::collections::HashMap;
+
+use crate::{
+    cards::share_url,
+    fetch_quotes,
+    public_url,
+    quote_from_row,
+    AppState,
+    Quote,
+    QuoteFilter,
+    QuoteSort,
+    QUOTE_COLUMNS,
+};
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+/// Entry titles are the start of the quote; the full text is the content.
+const TITLE_CHARS: usize = 80;
+const SITE_NAME: &str = "Rusted Quotes";
+const RSS_TYPE: &str = "application/rss+xml; charset=utf-8";
+const ATOM_TYPE: &str = "application/atom+xml; charset=utf-8";
+
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    tag: Option<String>,
+    author: Option<String>,
+    limit: Option<u64>,
+}
+
+struct FeedItem {
+    quote: Quote,
+    /// The day it was quote of the day; `None` in the latest feed.
+    day: Option<NaiveDate>,
+    published: DateTime<Utc>,
+}
+
+impl FeedItem {
+    fn id(&self) -> String {
+        match self.day {
+            None => format!("urn:uuid:{}", self.quote.id),
+            Some(day) => format!("tag:rusted-quotes,{}:daily/{}", day, self.quote.id),
+        }
+    }
+
+    fn updated(&self) -> DateTime<Utc> {
+        self.quote.updated_at.max(self.published)
+    }
+
+    fn title(&self) -> String {
+        let text = self.quote.quote.trim();
+        let mut title: String = text.chars().take(TITLE_CHARS).collect();
+        if title.len() < text.len() {
+            title.push('…');
+        }
+        match &self.quote.author {
+            Some(author) => format!("{} — {}", title, author),
+            None => title,
+        }
+    }
+}
+
+struct Feed {
+    title: String,
+    self_url: String,
+    items: Vec<FeedItem>,
+}
+
+impl Feed {
+    fn updated(&self) -> DateTime<Utc> {
+        self.items.iter().map(FeedItem::updated).max().unwrap_or_else(Utc::now)
+    }
+}
+
+fn feed_title(kind: &str, filter: &QuoteFilter, params: &FeedQuery) -> String {
+    let mut title = format!("{}: {}", SITE_NAME, kind);
+    if let Some(tag) = &filter.tag {
+        title.push_str(&format!(" tagged \"{}\"", tag));
+    }
+    // The filter holds the lowercased name; show it as it was asked for.
+    if filter.author.is_some() {
+        title.push_str(&format!(" by {}", params.author.as_deref().unwrap_or_default().trim()));
+    }
+    title
+}
+
+fn feed_filter(params: &FeedQuery) -> QuoteFilter {
+    QuoteFilter::new(None, params.tag.as_deref(), params.author.as_deref(), Vec::new())
+}
+
+fn feed_limit(params: &FeedQuery) -> u64 {
+    params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+async fn latest_feed(state: &AppState, params: &FeedQuery, uri: &Uri) -> Result<Feed, sqlx::Error> {
+    let filter = feed_filter(params);
+    let quotes = fetch_quotes(&state.pool, &filter, QuoteSort::Newest, None, None, feed_limit(params), 0).await?;
+
+    Ok(Feed {
+        title: feed_title("latest quotes", &filter, params),
+        self_url: format!("{}{}", public_url(), uri),
+        items: quotes
+            .into_iter()
+            .map(|quote| FeedItem { published: quote.created_at, day: None, quote })
+            .collect(),
+    })
+}
+
+async fn daily_feed(state: &AppState, params: &FeedQuery, uri: &Uri) -> Result<Feed, sqlx::Error> {
+    // Picks today's quote if nobody has asked for it yet.
+    state.daily.current(&state.pool).await?;
+
+    let days: Vec<(NaiveDate, String)> = sqlx
+        ::query("SELECT day, quote_id FROM daily_quotes WHERE day <= ? ORDER BY day DESC LIMIT ?")
+        .bind(state.daily.today())
+        .bind(feed_limit(params))
+        .fetch_all(&state.pool).await?
+        .iter()
+        .map(|row| (row.get("day"), row.get("quote_id")))
+        .collect();
+
+    let filter = feed_filter(params);
+    let mut quotes: HashMap<String, Quote> = HashMap::new();
+    if !days.is_empty() {
+        let mut conditions = filter.conditions();
+        conditions.push(format!("id IN ({})", vec!["?"; days.len()].join(", ")));
+        let sql = format!("SELECT {} FROM quotes WHERE {}", QUOTE_COLUMNS, conditions.join(" AND "));
+
+        let mut query = filter.bind(sqlx::query(&sql));
+        for (_, quote_id) in &days {
+            query = query.bind(quote_id);
+        }
+        for row in query.fetch_all(&state.pool).await? {
+            let quote = quote_from_row(&row);
+            quotes.insert(quote.id.clone(), quote);
+        }
+    }
+
+    Ok(Feed {
+        title: feed_title("quote of the day", &filter, params),
+        self_url: format!("{}{}", public_url(), uri),
+        items: days
+            .into_iter()
+            .filter_map(|(day, quote_id)| {
+                let quote = quotes.get(&quote_id)?.clone();
+                Some(FeedItem { published: state.daily.starts_at(day), day: Some(day), quote })
+            })
+            .collect(),
+    })
+}
+
+fn render_rss(feed: &Feed) -> String {
+    let base = public_url();
+    let items = feed.items
+        .iter()
+        .map(|item| rss::Item {
+            title: Some(item.title()),
+            link: Some(share_url(&item.quote.id)),
+            description: Some(item.quote.quote.clone()),
+            guid: Some(rss::Guid { value: item.id(), permalink: false }),
+            pub_date: Some(item.published.to_rfc2822()),
+            categories: item.quote.tags
+                .iter()
+                .map(|tag| rss::Category { name: tag.clone(), domain: None })
+                .collect(),
+            dublin_core_ext: item.quote.author.as_ref().map(|author| DublinCoreExtension {
+                creators: vec![author.clone()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    let channel = rss::Channel {
+        title: feed.title.clone(),
+        link: base.clone(),
+        description: feed.title.clone(),
+        last_build_date: Some(feed.updated().to_rfc2822()),
+        items,
+        namespaces: [("dc".to_string(), dublincore::NAMESPACE.to_string())].into(),
+        ..Default::default()
+    };
+    channel.to_string()
+}
+
+fn render_atom(feed: &Feed) -> String {
+    let base = public_url();
+    let entries = feed.items
+        .iter()
+        .map(|item| atom::Entry {
+            title: atom::Text::plain(item.title()),
+            id: item.id(),
+            updated: item.updated().fixed_offset(),
+            published: Some(item.published.fixed_offset()),
+            authors: item.quote.author
+                .iter()
+                .map(|author| atom::Person { name: author.clone(), ..Default::default() })
+                .collect(),
+            categories: item.quote.tags
+                .iter()
+                .map(|tag| atom::Category { term: tag.clone(), ..Default::default() })
+                .collect(),
+            links: vec![atom::Link { href: share_url(&item.quote.id), ..Default::default() }],
+            content: Some(atom::Content {
+                value: Some(item.quote.quote.clone()),
+                content_type: Some("text".to_string()),
+                lang: item.quote.language.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    let atom_feed = atom::Feed {
+        title: atom::Text::plain(feed.title.clone()),
+        id: feed.self_url.clone(),
+        updated: feed.updated().fixed_offset(),
+        // Covers entries without an author of their own.
+        authors: vec![atom::Person { name: SITE_NAME.to_string(), ..Default::default() }],
+        links: vec![
+            atom::Link {
+                href: feed.self_url.clone(),
+                rel: "self".to_string(),
+                mime_type: Some("application/atom+xml".to_string()),
+                ..Default::default()
+            },
+            atom::Link { href: base, ..Default::default() }
+        ],
+        entries,
+        ..Default::default()
+    };
+    atom_feed.to_string()
+}
+
+fn respond(
+    feed: Result<Feed, sqlx::Error>,
+    render: fn(&Feed) -> String,
+    content_type: &'static str
+) -> Result<impl IntoResponse, StatusCode> {
+    match feed {
+        Ok(feed) => Ok(([(CONTENT_TYPE, content_type)], render(&feed))),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn latest_rss(
+    State(state): State<AppState>,
+    uri: Uri,
+    Query(params): Query<FeedQuery>
+) -> Result<impl IntoResponse, StatusCode> {
+    respond(latest_feed(&state, &params, &uri).await, render_rss, RSS_TYPE)
+}
+
+pub async fn latest_atom(
+    State(state): State<AppState>,
+    uri: Uri,
+    Query(params): Query<FeedQuery>
+) -> Result<impl IntoResponse, StatusCode> {
+    respond(latest_feed(&state, &params, &uri).await, render_atom, ATOM_TYPE)
+}
+
+pub async fn daily_rss(
+    State(state): State<AppState>,
+    uri: Uri,
+    Query(params): Query<FeedQuery>
+) -> Result<impl IntoResponse, StatusCode> {
+    respond(daily_feed(&state, &params, &uri).await, render_rss, RSS_TYPE)
+}
+
+pub async fn daily_atom(
+    State(state): State<AppState>,
+    uri: Uri,
+    Query(params): Query<FeedQuery>
+) -> Result<impl IntoResponse, StatusCode> {
+    respond(daily_feed(&state, &params, &uri).await, render_atom, ATOM_TYPE)
+}
@@ -0,0 +1,339 @@
+//! Revision history for quotes.
+//!
+//! The first edit of a quote snapshots its original text as revision 1, and
+//! every edit after that appends the new version, so `quote_revisions` holds
+//! each version a quote has had along with who made it and when. A quote
+//! that was never edited has an implicit single revision built from its row.
+
+use axum::{ extract::{ Path, Query, State }, http::{ HeaderMap, StatusCode }, response::Json };
+use chrono::{ DateTime, Utc };
+use serde::{ Deserialize, Serialize };
+use similar::{ ChangeTag, TextDiff };
+use sqlx::{ mysql::{ MySqlPool, MySqlRow }, MySql, Row, Transaction };
+
+use crate::{
+    events::QuoteEvent,
+    fetch_quote,
+    protocol::ModerationAction,
+    quote_from_row,
+    replace_tags,
+    require_admin,
+    AppState,
+    Quote,
+    QUOTE_COLUMNS,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    revision: u32,
+    quote: String,
+    author: Option<String>,
+    tags: Vec<String>,
+    /// `None` for the original submission.
+    editor: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+/// The editable fields of a quote, already validated and normalized.
+pub struct QuoteContent {
+    pub quote: String,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    from: u32,
+    to: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffChunk {
+    op: &'static str,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    from: u32,
+    to: u32,
+    quote: Vec<DiffChunk>,
+    author: Option<FieldChange>,
+    tags_added: Vec<String>,
+    tags_removed: Vec<String>,
+}
+
+pub async fn create_table(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS quote_revisions (
+            quote_id VARCHAR(36) NOT NULL,
+            revision INT UNSIGNED NOT NULL,
+            quote TEXT NOT NULL,
+            author VARCHAR(255),
+            tags TEXT,
+            editor VARCHAR(100),
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (quote_id, revision)
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+fn split_tags(tags: Option<String>) -> Vec<String> {
+    tags.filter(|tags| !tags.is_empty())
+        .map(|tags| tags.split(',').map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn revision_from_row(row: &MySqlRow) -> Revision {
+    Revision {
+        revision: row.get("revision"),
+        quote: row.get("quote"),
+        author: row.get("author"),
+        tags: split_tags(row.get("tags")),
+        editor: row.get("editor"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn original_revision(quote: &Quote) -> Revision {
+    Revision {
+        revision: 1,
+        quote: quote.quote.clone(),
+        author: quote.author.clone(),
+        tags: quote.tags.clone(),
+        editor: None,
+        created_at: quote.created_at,
+    }
+}
+
+async fn load_history(pool: &MySqlPool, id: &str) -> Result<Option<Vec<Revision>>, sqlx::Error> {
+    let Some(quote) = fetch_quote(pool, id).await? else {
+        return Ok(None);
+    };
+
+    let revisions: Vec<Revision> = sqlx
+        ::query(
+            "SELECT revision, quote, author, tags, editor, created_at
+             FROM quote_revisions WHERE quote_id = ? ORDER BY revision"
+        )
+        .bind(id)
+        .fetch_all(pool).await?
+        .iter()
+        .map(revision_from_row)
+        .collect();
+
+    if revisions.is_empty() {
+        Ok(Some(vec![original_revision(&quote)]))
+    } else {
+        Ok(Some(revisions))
+    }
+}
+
+/// Applies `content` to the quote and records it as a new revision. Returns
+/// `None` when the quote does not exist.
+pub async fn apply_edit(
+    pool: &MySqlPool,
+    id: &str,
+    content: QuoteContent,
+    editor: &str
+) -> Result<Option<Quote>, sqlx::Error> {
+    let mut tx: Transaction<'_, MySql> = pool.begin().await?;
+
+    let current = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL FOR UPDATE", QUOTE_COLUMNS))
+        .bind(id)
+        .fetch_optional(&mut *tx).await?
+        .map(|row| quote_from_row(&row));
+    let Some(current) = current else {
+        return Ok(None);
+    };
+
+    let latest: Option<u32> = sqlx
+        ::query_scalar(
+            "SELECT revision FROM quote_revisions WHERE quote_id = ? ORDER BY revision DESC LIMIT 1"
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx).await?;
+
+    let next = match latest {
+        Some(latest) => latest + 1,
+        None => {
+            let original = original_revision(&current);
+            sqlx::query(
+                "INSERT INTO quote_revisions (quote_id, revision, quote, author, tags, editor, created_at)
+                 VALUES (?, 1, ?, ?, ?, NULL, ?)"
+            )
+                .bind(id)
+                .bind(&original.quote)
+                .bind(&original.author)
+                .bind(original.tags.join(","))
+                .bind(original.created_at)
+                .execute(&mut *tx).await?;
+            2
+        }
+    };
+
+    let now = Utc::now();
+    sqlx::query("UPDATE quotes SET quote = ?, author = ?, updated_at = ? WHERE id = ?")
+        .bind(&content.quote)
+        .bind(&content.author)
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx).await?;
+    replace_tags(&mut tx, id, &content.tags).await?;
+
+    sqlx::query(
+        "INSERT INTO quote_revisions (quote_id, revision, quote, author, tags, editor, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+        .bind(id)
+        .bind(next)
+        .bind(&content.quote)
+        .bind(&content.author)
+        .bind(content.tags.join(","))
+        .bind(editor)
+        .bind(now)
+        .execute(&mut *tx).await?;
+
+    tx.commit().await?;
+
+    Ok(
+        Some(Quote {
+            quote: content.quote,
+            author: content.author,
+            tags: content.tags,
+            updated_at: now,
+            ..current
+        })
+    )
+}
+
+fn diff_revisions(from: &Revision, to: &Revision) -> RevisionDiff {
+    let mut chunks: Vec<DiffChunk> = Vec::new();
+    let diff = TextDiff::from_words(&from.quote, &to.quote);
+
+    for change in diff.iter_all_changes() {
+        let op = match change.tag() {
+            ChangeTag::Equal => "equal",
+            ChangeTag::Insert => "insert",
+            ChangeTag::Delete => "delete",
+        };
+        match chunks.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(change.value()),
+            _ => chunks.push(DiffChunk { op, text: change.value().to_string() }),
+        }
+    }
+
+    RevisionDiff {
+        from: from.revision,
+        to: to.revision,
+        quote: chunks,
+        author: (from.author != to.author).then(|| FieldChange {
+            from: from.author.clone(),
+            to: to.author.clone(),
+        }),
+        tags_added: to.tags
+            .iter()
+            .filter(|tag| !from.tags.contains(tag))
+            .cloned()
+            .collect(),
+        tags_removed: from.tags
+            .iter()
+            .filter(|tag| !to.tags.contains(tag))
+            .cloned()
+            .collect(),
+    }
+}
+
+pub async fn get_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>
+) -> Result<Json<Vec<Revision>>, StatusCode> {
+    match load_history(&state.pool, &id).await {
+        Ok(Some(mut revisions)) => {
+            revisions.reverse();
+            Ok(Json(revisions))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn get_diff(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<DiffQuery>
+) -> Result<Json<RevisionDiff>, StatusCode> {
+    let revisions = match load_history(&state.pool, &id).await {
+        Ok(Some(revisions)) => revisions,
+        Ok(None) => {
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let find = |number: u32| revisions.iter().find(|revision| revision.revision == number);
+    match (find(params.from), find(params.to)) {
+        (Some(from), Some(to)) => Ok(Json(diff_revisions(from, to))),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+pub async fn revert_to_revision(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, revision)): Path<(String, u32)>
+) -> Result<Json<Quote>, StatusCode> {
+    require_admin(&headers)?;
+
+    let target = match load_history(&state.pool, &id).await {
+        Ok(Some(revisions)) => revisions.into_iter().find(|r| r.revision == revision),
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let Some(target) = target else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let content = QuoteContent {
+        quote: target.quote,
+        author: target.author,
+        tags: target.tags,
+    };
+    let editor = format!("admin (revert to r{})", revision);
+
+    match apply_edit(&state.pool, &id, content, &editor).await {
+        Ok(Some(quote)) => {
+            state.random.invalidate().await;
+            state.daily.forget_quote(&id).await;
+            state.events.publish(QuoteEvent::Updated { quote: quote.clone() });
+            state.live.moderation(ModerationAction::Reverted, Some(&id), Some(&editor));
+            Ok(Json(quote))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
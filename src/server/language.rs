@@ -0,0 +1,320 @@
+//! Quote languages and translations.
+//!
+//! Every quote carries a BCP 47 `language` tag, either given on submission or
+//! detected from the text. A translation is an ordinary quote whose
+//! `translation_of` points at the original; the original and all its
+//! translations form one group. The main listing shows originals only unless
+//! a `lang` filter asks for specific languages.
+
+use axum::{ extract::{ Path, State }, http::{ header::ACCEPT_LANGUAGE, HeaderMap, StatusCode }, response::Json };
+use language_tags::LanguageTag;
+use sqlx::{ mysql::MySqlPool, Executor, MySql, Row };
+use whatlang::Lang;
+
+use crate::{ quote_from_row, AppState, Quote, QUOTE_COLUMNS };
+
+/// Upper bound on the languages taken from one `lang` parameter or
+/// `Accept-Language` header.
+const MAX_LANGUAGES: usize = 8;
+
+/// Parses and case-normalizes a BCP 47 tag ("en-us" becomes "en-US").
+pub fn normalize_language(tag: &str) -> Option<String> {
+    LanguageTag::parse(tag.trim())
+        .ok()
+        .filter(|tag| tag.is_language_range() && tag.validate().is_ok())
+        .map(LanguageTag::into_string)
+        .filter(|tag| tag.len() <= 35)
+}
+
+/// BCP 47 prefers the two-letter ISO 639-1 code wherever one exists.
+fn bcp47_code(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Epo => "eo",
+        Lang::Eng => "en",
+        Lang::Rus => "ru",
+        Lang::Cmn => "zh",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ben => "bn",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ukr => "uk",
+        Lang::Kat => "ka",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Jpn => "ja",
+        Lang::Heb => "he",
+        Lang::Yid => "yi",
+        Lang::Pol => "pl",
+        Lang::Amh => "am",
+        Lang::Jav => "jv",
+        Lang::Kor => "ko",
+        Lang::Nob => "nb",
+        Lang::Dan => "da",
+        Lang::Swe => "sv",
+        Lang::Fin => "fi",
+        Lang::Tur => "tr",
+        Lang::Nld => "nl",
+        Lang::Hun => "hu",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Bul => "bg",
+        Lang::Bel => "be",
+        Lang::Mar => "mr",
+        Lang::Kan => "kn",
+        Lang::Ron => "ro",
+        Lang::Slv => "sl",
+        Lang::Hrv => "hr",
+        Lang::Srp => "sr",
+        Lang::Mkd => "mk",
+        Lang::Lit => "lt",
+        Lang::Lav => "lv",
+        Lang::Est => "et",
+        Lang::Tam => "ta",
+        Lang::Vie => "vi",
+        Lang::Urd => "ur",
+        Lang::Tha => "th",
+        Lang::Guj => "gu",
+        Lang::Uzb => "uz",
+        Lang::Pan => "pa",
+        Lang::Aze => "az",
+        Lang::Ind => "id",
+        Lang::Tel => "te",
+        Lang::Pes => "fa",
+        Lang::Mal => "ml",
+        Lang::Ori => "or",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Sin => "si",
+        Lang::Khm => "km",
+        Lang::Tuk => "tk",
+        Lang::Aka => "ak",
+        Lang::Zul => "zu",
+        Lang::Sna => "sn",
+        Lang::Afr => "af",
+        Lang::Lat => "la",
+        Lang::Slk => "sk",
+        Lang::Cat => "ca",
+        Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+    }
+}
+
+/// Guesses the language of `text`. Short or mixed quotes often can't be told
+/// apart reliably; those are left untagged rather than guessed wrong.
+pub fn detect_language(text: &str) -> Option<String> {
+    whatlang
+        ::detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| bcp47_code(info.lang()).to_string())
+}
+
+/// The submitted tag if there is one, otherwise the detected language.
+pub fn resolve_language(given: Option<&str>, text: &str) -> Result<Option<String>, StatusCode> {
+    match given.map(str::trim).filter(|tag| !tag.is_empty()) {
+        Some(tag) => normalize_language(tag).map(Some).ok_or(StatusCode::BAD_REQUEST),
+        None => Ok(detect_language(text)),
+    }
+}
+
+/// Languages from an `Accept-Language` header, most preferred first.
+fn accepted_languages(headers: &HeaderMap) -> Vec<String> {
+    let Some(header) = headers.get(ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok()) else {
+        return Vec::new();
+    };
+
+    let mut ranges: Vec<(f32, usize, String)> = header
+        .split(',')
+        .enumerate()
+        .filter_map(|(order, range)| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if tag == "*" || quality <= 0.0 {
+                return None;
+            }
+            Some((quality, order, normalize_language(tag)?))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let mut languages: Vec<String> = Vec::new();
+    for (_, _, tag) in ranges {
+        if !languages.contains(&tag) {
+            languages.push(tag);
+        }
+    }
+    languages.truncate(MAX_LANGUAGES);
+    languages
+}
+
+/// Resolves a `?lang=` value: a comma-separated list of tags, or `auto` to
+/// use the caller's `Accept-Language`. An empty result means "no filter".
+pub fn requested_languages(lang: Option<&str>, headers: &HeaderMap) -> Result<Vec<String>, StatusCode> {
+    match lang.map(str::trim).filter(|lang| !lang.is_empty()) {
+        None => Ok(Vec::new()),
+        Some("auto") => Ok(accepted_languages(headers)),
+        Some(list) => {
+            let mut languages: Vec<String> = list
+                .split(',')
+                .map(|tag| normalize_language(tag).ok_or(StatusCode::BAD_REQUEST))
+                .collect::<Result<_, _>>()?;
+            // Every tag is checked, not just the ones that are kept.
+            languages.truncate(MAX_LANGUAGES);
+            Ok(languages)
+        }
+    }
+}
+
+/// SQL matching any of `count` language ranges, RFC 4647 basic filtering
+/// style: "en" matches "en" and "en-GB". Bind each range twice.
+pub fn language_filter(count: usize) -> String {
+    let ranges = vec!["(language = ? OR language LIKE CONCAT(?, '-%'))"; count];
+    format!("({})", ranges.join(" OR "))
+}
+
+pub async fn save_language<'e, E>(executor: E, quote_id: &str, language: Option<&str>) -> Result<(), sqlx::Error>
+    where E: Executor<'e, Database = MySql>
+{
+    sqlx::query("UPDATE quotes SET language = ?, updated_at = updated_at WHERE id = ?")
+        .bind(language)
+        .bind(quote_id)
+        .execute(executor).await?;
+
+    Ok(())
+}
+
+/// Tags quotes stored before languages existed.
+pub async fn backfill_languages(pool: &MySqlPool) -> Result<u64, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, quote FROM quotes WHERE language IS NULL").fetch_all(pool).await?;
+
+    let mut tagged = 0;
+    for row in rows {
+        let quote: String = row.get("quote");
+        if let Some(language) = detect_language(&quote) {
+            let id: String = row.get("id");
+            save_language(pool, &id, Some(&language)).await?;
+            tagged += 1;
+        }
+    }
+
+    Ok(tagged)
+}
+
+/// The id of the original a new translation should link to: `id` itself, or
+/// its original if `id` is already a translation. `None` if it doesn't exist.
+pub async fn translation_root<'e, E>(executor: E, id: &str) -> Result<Option<String>, sqlx::Error>
+    where E: Executor<'e, Database = MySql>
+{
+    sqlx::query_scalar(
+        "SELECT COALESCE(translation_of, id) FROM quotes WHERE id = ? AND deleted_at IS NULL"
+    )
+        .bind(id)
+        .fetch_optional(executor).await
+}
+
+/// Lists the other versions of a quote: its original and every translation,
+/// original first.
+pub async fn get_translations(
+    State(state): State<AppState>,
+    Path(id): Path<String>
+) -> Result<Json<Vec<Quote>>, StatusCode> {
+    let result = async {
+        let Some(root) = translation_root(&state.pool, &id).await? else {
+            return Ok(None);
+        };
+
+        let rows = sqlx
+            ::query(
+                &format!(
+                    "SELECT {} FROM quotes
+                     WHERE (id = ? OR translation_of = ?) AND id <> ? AND deleted_at IS NULL
+                     ORDER BY translation_of IS NOT NULL, language",
+                    QUOTE_COLUMNS
+                )
+            )
+            .bind(&root)
+            .bind(&root)
+            .bind(&id)
+            .fetch_all(&state.pool).await?;
+        Ok::<_, sqlx::Error>(Some(rows.iter().map(quote_from_row).collect()))
+    }.await;
+
+    match result {
+        Ok(Some(quotes)) => Ok(Json(quotes)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn tags_are_case_normalized() {
+        assert_eq!(normalize_language(" en-us "), Some("en-US".to_string()));
+        assert_eq!(normalize_language("ZH-hant-tw"), Some("zh-Hant-TW".to_string()));
+        assert_eq!(normalize_language("not a tag"), None);
+        assert_eq!(normalize_language(""), None);
+    }
+
+    #[test]
+    fn accepted_languages_follow_their_quality() {
+        let headers = accept("fr;q=0.5, en-GB, de;q=0.8, es;q=0.5");
+        assert_eq!(accepted_languages(&headers), ["en-GB", "de", "fr", "es"]);
+    }
+
+    #[test]
+    fn wildcards_and_refused_languages_are_skipped() {
+        let headers = accept("*, it;q=0, pt;q=0.0, nl;q=0.3, ??");
+        assert_eq!(accepted_languages(&headers), ["nl"]);
+        assert!(accepted_languages(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn accepted_languages_are_deduplicated() {
+        let headers = accept("en-us;q=0.4, EN-US;q=0.9, en");
+        assert_eq!(accepted_languages(&headers), ["en", "en-US"]);
+    }
+
+    #[test]
+    fn accepted_languages_are_capped() {
+        let languages = accepted_languages(&accept("aa, ab, ae, af, ak, am, an, ar, as, av"));
+        assert_eq!(languages, ["aa", "ab", "ae", "af", "ak", "am", "an", "ar"]);
+    }
+
+    #[test]
+    fn requested_languages_come_from_the_parameter_or_the_header() {
+        let headers = accept("de, en;q=0.5");
+        assert_eq!(requested_languages(None, &headers), Ok(Vec::new()));
+        assert_eq!(requested_languages(Some("  "), &headers), Ok(Vec::new()));
+        assert_eq!(requested_languages(Some("auto"), &headers), Ok(vec!["de".to_string(), "en".to_string()]));
+        assert_eq!(requested_languages(Some("fr-ca,ja"), &headers), Ok(vec!["fr-CA".to_string(), "ja".to_string()]));
+    }
+
+    #[test]
+    fn every_requested_language_is_validated() {
+        let headers = HeaderMap::new();
+        assert_eq!(requested_languages(Some("en,not a tag"), &headers), Err(StatusCode::BAD_REQUEST));
+
+        let valid = ["aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as"].join(",");
+        assert_eq!(requested_languages(Some(&valid), &headers).map(|languages| languages.len()), Ok(MAX_LANGUAGES));
+        let invalid = format!("{},not a tag", valid);
+        assert_eq!(requested_languages(Some(&invalid), &headers), Err(StatusCode::BAD_REQUEST));
+    }
+}
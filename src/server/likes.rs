@@ -0,0 +1,146 @@
+//! Likes on quotes.
+//!
+//! There are no user accounts, so a like is keyed on the caller's
+//! [`client_fingerprint`](crate::client_fingerprint). Rows record which
+//! version of the fingerprint made them: when it changes, old rows can no
+//! longer be matched to anyone, so they are dropped at startup and the like
+//! counts recounted from what is left.
+
+use axum::{ extract::{ ConnectInfo, Path, State }, http::{ HeaderMap, StatusCode }, response::Json };
+use serde::Serialize;
+use sqlx::mysql::MySqlPool;
+use std::net::SocketAddr;
+
+use crate::{ add_column_if_missing, client_fingerprint, AppState };
+
+/// Bumped whenever `client_fingerprint` hashes something different.
+const FINGERPRINT_VERSION: u8 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct LikeStatus {
+    id: String,
+    like_count: u32,
+    liked: bool,
+}
+
+pub async fn create_table(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS quote_likes (
+            quote_id VARCHAR(36) NOT NULL,
+            fingerprint CHAR(64) NOT NULL,
+            fingerprint_version TINYINT UNSIGNED NOT NULL DEFAULT 0,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (quote_id, fingerprint)
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    add_column_if_missing(pool, "quote_likes", "fingerprint_version", "TINYINT UNSIGNED NOT NULL DEFAULT 0").await?;
+
+    let stale = sqlx
+        ::query("DELETE FROM quote_likes WHERE fingerprint_version <> ?")
+        .bind(FINGERPRINT_VERSION)
+        .execute(pool).await?
+        .rows_affected();
+    if stale > 0 {
+        sqlx::query(
+            "UPDATE quotes SET updated_at = updated_at,
+             like_count = (SELECT COUNT(*) FROM quote_likes WHERE quote_likes.quote_id = quotes.id)"
+        )
+            .execute(pool).await?;
+    }
+
+    Ok(())
+}
+
+async fn set_like(
+    state: &AppState,
+    id: &str,
+    fingerprint: &str,
+    liked: bool
+) -> Result<Option<u32>, sqlx::Error> {
+    let mut tx = state.pool.begin().await?;
+
+    let exists: Option<u32> = sqlx
+        ::query_scalar("SELECT like_count FROM quotes WHERE id = ? AND deleted_at IS NULL FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx).await?;
+    if exists.is_none() {
+        return Ok(None);
+    }
+
+    let changed = if liked {
+        sqlx::query("INSERT IGNORE INTO quote_likes (quote_id, fingerprint, fingerprint_version) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(fingerprint)
+            .bind(FINGERPRINT_VERSION)
+            .execute(&mut *tx).await?
+            .rows_affected()
+    } else {
+        sqlx::query("DELETE FROM quote_likes WHERE quote_id = ? AND fingerprint = ?")
+            .bind(id)
+            .bind(fingerprint)
+            .execute(&mut *tx).await?
+            .rows_affected()
+    };
+
+    if changed > 0 {
+        let update = if liked {
+            "UPDATE quotes SET like_count = like_count + 1, updated_at = updated_at WHERE id = ?"
+        } else {
+            "UPDATE quotes SET like_count = GREATEST(like_count, 1) - 1, updated_at = updated_at WHERE id = ?"
+        };
+        sqlx::query(update).bind(id).execute(&mut *tx).await?;
+    }
+
+    let like_count: u32 = sqlx
+        ::query_scalar("SELECT like_count FROM quotes WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx).await?;
+
+    tx.commit().await?;
+    Ok(Some(like_count))
+}
+
+async fn respond(
+    state: AppState,
+    id: String,
+    headers: HeaderMap,
+    addr: SocketAddr,
+    liked: bool
+) -> Result<Json<LikeStatus>, StatusCode> {
+    let fingerprint = client_fingerprint(&headers, addr);
+
+    match set_like(&state, &id, &fingerprint, liked).await {
+        Ok(Some(like_count)) => {
+            state.random.set_like_count(&id, like_count).await;
+            state.live.like_count(&id, like_count);
+            Ok(Json(LikeStatus { id, like_count, liked }))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn like_quote(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<Json<LikeStatus>, StatusCode> {
+    respond(state, id, headers, addr, true).await
+}
+
+pub async fn unlike_quote(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<Json<LikeStatus>, StatusCode> {
+    respond(state, id, headers, addr, false).await
+}
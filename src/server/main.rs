@@ -0,0 +1,917 @@
+mod cards;
+mod citation;
+mod collections;
+mod csp;
+mod daily;
+mod embed;
+mod events;
+mod feeds;
+mod history;
+mod language;
+mod likes;
+mod pagination;
+#[path = "../protocol.rs"]
+mod protocol;
+mod random;
+mod raster;
+mod trash;
+mod webhooks;
+mod ws;
+
+use axum::{
+    extract::{ Query, State },
+    http::{ header::AUTHORIZATION, HeaderMap, Method, StatusCode },
+    response::Json,
+    routing::{ delete, get, post, put },
+    Router,
+};
+use serde::{ Deserialize, Serialize };
+use sha2::{ Digest, Sha256 };
+use sqlx::{ mysql::{ MySqlArguments, MySqlPool, MySqlRow }, query::Query as SqlQuery, MySql, Row, Transaction };
+use std::{ env, net::{ IpAddr, Ipv6Addr, SocketAddr }, sync::Arc };
+use tower_http::cors::{ Any, CorsLayer };
+use uuid::Uuid;
+use chrono::{ DateTime, Utc };
+
+use pagination::{ Cursor, SortKey };
+use protocol::ModerationAction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Quote {
+    id: String,
+    quote: String,
+    author: Option<String>,
+    tags: Vec<String>,
+    like_count: u32,
+    source: Option<citation::Source>,
+    /// BCP 47 tag; `None` when it was not given and could not be detected.
+    language: Option<String>,
+    /// The original this quote is a translation of.
+    translation_of: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateQuote {
+    quote: String,
+    author: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// `verified` is only honoured for admin callers.
+    source: Option<citation::Source>,
+    /// Detected from the text when omitted.
+    language: Option<String>,
+    /// Submits this quote as a translation of an existing one.
+    translation_of: Option<String>,
+    /// Chosen by the client so a retried submission (e.g. one queued while
+    /// offline) returns the quote it already created instead of a copy.
+    submission_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateQuote {
+    quote: Option<String>,
+    /// An empty string clears the author.
+    author: Option<String>,
+    tags: Option<Vec<String>>,
+    /// Replaces the whole source; `{}` clears it.
+    source: Option<citation::Source>,
+    /// An empty string re-detects the language from the text.
+    language: Option<String>,
+    /// Recorded in the revision history; defaults to "admin".
+    editor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum QuoteSort {
+    #[default]
+    Newest,
+    Oldest,
+    Popular,
+    Random,
+}
+
+impl QuoteSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            QuoteSort::Newest => "created_at DESC, id DESC",
+            QuoteSort::Oldest => "created_at ASC, id ASC",
+            QuoteSort::Popular => "like_count DESC, created_at DESC, id DESC",
+            // Seeded so that paging through a shuffled list stays consistent.
+            QuoteSort::Random => "RAND(?)",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteQuery {
+    page: Option<u64>,
+    limit: Option<u64>,
+    /// From the `X-Next-Cursor` header of the previous page; takes the
+    /// place of `page`. See `pagination`.
+    cursor: Option<String>,
+    search: Option<String>,
+    sort: Option<QuoteSort>,
+    seed: Option<u32>,
+    /// Comma-separated BCP 47 tags, or `auto` for the `Accept-Language` header.
+    lang: Option<String>,
+    tag: Option<String>,
+    /// Exact author name, case-insensitive.
+    author: Option<String>,
+}
+
+/// The `WHERE` clause shared by the quote listing and the feeds.
+#[derive(Debug, Default)]
+struct QuoteFilter {
+    search: Option<String>,
+    tag: Option<String>,
+    author: Option<String>,
+    languages: Vec<String>,
+}
+
+impl QuoteFilter {
+    fn new(
+        search: Option<&str>,
+        tag: Option<&str>,
+        author: Option<&str>,
+        languages: Vec<String>
+    ) -> QuoteFilter {
+        QuoteFilter {
+            search: search.map(|search| format!("%{}%", search)),
+            tag: tag.and_then(|tag| normalize_tags(&[tag.to_string()]).pop()),
+            author: author
+                .map(|author| author.trim().to_lowercase())
+                .filter(|author| !author.is_empty()),
+            languages,
+        }
+    }
+
+    fn conditions(&self) -> Vec<String> {
+        let mut conditions = vec!["deleted_at IS NULL".to_string()];
+        if self.search.is_some() {
+            conditions.push("(quote LIKE ? OR author LIKE ?)".to_string());
+        }
+        if self.tag.is_some() {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM quote_tags WHERE quote_tags.quote_id = quotes.id AND tag = ?)".to_string()
+            );
+        }
+        if self.author.is_some() {
+            conditions.push("LOWER(TRIM(author)) = ?".to_string());
+        }
+        // Translations only show up when asked for by language; otherwise each
+        // group is listed once, as its original.
+        if self.languages.is_empty() {
+            conditions.push("translation_of IS NULL".to_string());
+        } else {
+            conditions.push(language::language_filter(self.languages.len()));
+        }
+        conditions
+    }
+
+    /// Binds the values for `conditions`, in the same order.
+    fn bind<'q>(&'q self, mut query: SqlQuery<'q, MySql, MySqlArguments>) -> SqlQuery<'q, MySql, MySqlArguments> {
+        if let Some(search) = &self.search {
+            query = query.bind(search).bind(search);
+        }
+        if let Some(tag) = &self.tag {
+            query = query.bind(tag);
+        }
+        if let Some(author) = &self.author {
+            query = query.bind(author);
+        }
+        for language in &self.languages {
+            query = query.bind(language).bind(language);
+        }
+        query
+    }
+}
+
+struct App {
+    pool: MySqlPool,
+    daily: daily::DailyState,
+    random: random::RandomPool,
+    events: events::EventHub,
+    live: ws::LiveHub,
+}
+
+type AppState = Arc<App>;
+
+const MAX_SUBMISSION_ID_LENGTH: usize = 64;
+
+const QUOTE_COLUMNS: &str =
+    "id, quote, author, like_count, created_at, updated_at,
+     source_title, source_year, source_url, source_page, source_verified, language, translation_of,
+     (SELECT GROUP_CONCAT(tag ORDER BY tag) FROM quote_tags WHERE quote_tags.quote_id = quotes.id) AS tags";
+
+fn quote_from_row(row: &MySqlRow) -> Quote {
+    let tags: Option<String> = row.get("tags");
+
+    Quote {
+        id: row.get("id"),
+        quote: row.get("quote"),
+        author: row.get("author"),
+        tags: tags
+            .map(|tags| tags.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+        like_count: row.get("like_count"),
+        source: citation::source_from_row(row),
+        language: row.get("language"),
+        translation_of: row.get("translation_of"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// The address this server is reachable at, without a trailing slash. Used
+/// wherever an absolute link leaves the site, e.g. in feeds.
+fn public_url() -> String {
+    env::var("PUBLIC_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "http://localhost:3000".to_string())
+}
+
+/// Where the web app itself is served, for links back from server pages.
+fn app_url() -> String {
+    env::var("APP_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "http://localhost:8080".to_string())
+}
+
+/// Escapes text for HTML and XML, in element content and quoted attributes.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Tags are stored lowercase and comma-free so they can round-trip through
+/// `GROUP_CONCAT` and be matched case-insensitively.
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase().replace(',', ""))
+        .filter(|tag| !tag.is_empty() && tag.chars().count() <= 50)
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// MySQL has no `ADD COLUMN IF NOT EXISTS`, so tables created by older
+/// versions are upgraded by checking `information_schema` first.
+async fn add_column_if_missing(
+    pool: &MySqlPool,
+    table: &str,
+    column: &str,
+    definition: &str
+) -> Result<(), sqlx::Error> {
+    let exists: i64 = sqlx
+        ::query_scalar(
+            "SELECT COUNT(*) FROM information_schema.columns
+             WHERE table_schema = DATABASE() AND table_name = ? AND column_name = ?"
+        )
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool).await?;
+
+    if exists == 0 {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool).await?;
+    }
+
+    Ok(())
+}
+
+async fn replace_tags(
+    tx: &mut Transaction<'_, MySql>,
+    quote_id: &str,
+    tags: &[String]
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM quote_tags WHERE quote_id = ?")
+        .bind(quote_id)
+        .execute(&mut **tx).await?;
+
+    for tag in tags {
+        sqlx::query("INSERT INTO quote_tags (quote_id, tag) VALUES (?, ?)")
+            .bind(quote_id)
+            .bind(tag)
+            .execute(&mut **tx).await?;
+    }
+
+    Ok(())
+}
+
+fn contains_inappropriate_content(text: &str) -> bool {
+    let inappropriate_words = env
+        ::var("INAPPROPRIATE_WORDS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .collect::<Vec<_>>();
+
+    let text_lower = text.to_lowercase();
+    inappropriate_words.iter().any(|word| { !word.is_empty() && text_lower.contains(word) })
+}
+
+/// With no `ADMIN_TOKEN` configured nothing is accepted.
+fn is_admin_token(provided: &str) -> bool {
+    let token = env::var("ADMIN_TOKEN").unwrap_or_default();
+    !token.is_empty() && provided == token
+}
+
+/// Admin endpoints expect `Authorization: Bearer <ADMIN_TOKEN>`.
+fn require_admin(headers: &HeaderMap) -> Result<(), StatusCode> {
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if is_admin_token(provided) => Ok(()),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+fn client_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-client-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Proxies allowed to say who they forward for, from the comma-separated
+/// `TRUSTED_PROXIES`. With none configured `X-Forwarded-For` is ignored.
+fn trusted_proxies() -> Vec<IpAddr> {
+    env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|proxy| proxy.trim().parse().ok())
+        .collect()
+}
+
+/// The address a request came from. Behind a trusted proxy this is the last
+/// `X-Forwarded-For` hop that is not one of the trusted proxies themselves,
+/// since anything before it was written by the client.
+fn client_address(headers: &HeaderMap, addr: SocketAddr) -> IpAddr {
+    let proxies = trusted_proxies();
+    if !proxies.contains(&addr.ip()) {
+        return addr.ip();
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect();
+    forwarded
+        .into_iter()
+        .rev()
+        .find(|hop| !proxies.contains(hop))
+        .unwrap_or(addr.ip())
+}
+
+/// Identifies an anonymous caller: the `X-Client-Id` the web UI keeps in
+/// local storage, so people sharing a network each count, together with the
+/// caller's address, which unlike a header it cannot pick freely. IPv6
+/// callers are grouped by their /64, the block a single host is usually
+/// handed. Only this hash is ever stored.
+fn client_fingerprint(headers: &HeaderMap, addr: SocketAddr) -> String {
+    let address = match client_address(headers, addr) {
+        IpAddr::V4(ip) => format!("addr:{}", ip),
+        IpAddr::V6(ip) => format!("addr:{}/64", Ipv6Addr::from(u128::from(ip) & (!0 << 64))),
+    };
+    let source = match client_id(headers) {
+        Some(client_id) => format!("client:{}|{}", client_id, address),
+        None => address,
+    };
+
+    hex::encode(Sha256::digest(source.as_bytes()))
+}
+
+async fn create_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateQuote>
+) -> Result<Json<Quote>, StatusCode> {
+    if
+        contains_inappropriate_content(&payload.quote) ||
+        payload.author.as_ref().is_some_and(|a| contains_inappropriate_content(a)) ||
+        payload.tags.iter().any(|t| contains_inappropriate_content(t))
+    {
+        state.live.moderation(ModerationAction::Rejected, None, Some(&payload.quote));
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let submission_id = payload.submission_id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+    if submission_id.is_some_and(|id| id.len() > MAX_SUBMISSION_ID_LENGTH) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(submission_id) = submission_id {
+        if let Some(existing) = find_submission(&state.pool, submission_id).await? {
+            return Ok(Json(existing));
+        }
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let tags = normalize_tags(&payload.tags);
+    let mut source = match payload.source {
+        Some(source) => citation::normalize_source(source)?,
+        None => None,
+    };
+    if let Some(source) = &mut source {
+        source.verified = source.verified && require_admin(&headers).is_ok();
+    }
+    let language = language::resolve_language(payload.language.as_deref(), &payload.quote)?;
+
+    let translation_of = match &payload.translation_of {
+        Some(original) => {
+            // A translation has to say what it is in, and a group holds one
+            // version per language.
+            let language = language.as_deref().ok_or(StatusCode::BAD_REQUEST)?;
+            let root = language
+                ::translation_root(&state.pool, original).await
+                .map_err(|e| {
+                    eprintln!("Database error: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            let taken: i64 = sqlx
+                ::query_scalar(
+                    "SELECT COUNT(*) FROM quotes
+                     WHERE (id = ? OR translation_of = ?) AND language = ? AND deleted_at IS NULL"
+                )
+                .bind(&root)
+                .bind(&root)
+                .bind(language)
+                .fetch_one(&state.pool).await
+                .map_err(|e| {
+                    eprintln!("Database error: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            if taken > 0 {
+                return Err(StatusCode::CONFLICT);
+            }
+            Some(root)
+        }
+        None => None,
+    };
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO quotes (id, quote, author, language, translation_of, submission_id, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(&id)
+            .bind(&payload.quote)
+            .bind(&payload.author)
+            .bind(&language)
+            .bind(&translation_of)
+            .bind(submission_id)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx).await?;
+        replace_tags(&mut tx, &id, &tags).await?;
+        if source.is_some() {
+            citation::save_source(&mut *tx, &id, source.as_ref()).await?;
+        }
+
+        tx.commit().await
+    }.await;
+
+    match result {
+        Ok(_) => {
+            let quote = Quote {
+                id,
+                quote: payload.quote,
+                author: payload.author,
+                tags,
+                like_count: 0,
+                source,
+                language,
+                translation_of,
+                created_at: now,
+                updated_at: now,
+            };
+            if quote.translation_of.is_none() {
+                state.random.insert(&quote).await;
+            }
+            state.events.publish(events::QuoteEvent::Created { quote: quote.clone() });
+            Ok(Json(quote))
+        }
+        Err(e) => {
+            // The same submission arrived twice at once; the other one won.
+            let duplicate = e.as_database_error().is_some_and(|e| e.is_unique_violation());
+            if let Some(submission_id) = submission_id.filter(|_| duplicate) {
+                return find_submission(&state.pool, submission_id).await?
+                    .map(Json)
+                    .ok_or(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// The quote an earlier request with this `submission_id` created, or
+/// `410 Gone` if it has since been deleted.
+async fn find_submission(pool: &MySqlPool, submission_id: &str) -> Result<Option<Quote>, StatusCode> {
+    let row = sqlx
+        ::query("SELECT id, deleted_at IS NOT NULL AS deleted FROM quotes WHERE submission_id = ?")
+        .bind(submission_id)
+        .fetch_optional(pool).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    if row.get::<bool, _>("deleted") {
+        return Err(StatusCode::GONE);
+    }
+
+    fetch_quote(pool, &row.get::<String, _>("id")).await.map_err(|e| {
+        eprintln!("Database error: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+async fn update_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Json(payload): Json<UpdateQuote>
+) -> Result<Json<Quote>, StatusCode> {
+    require_admin(&headers)?;
+
+    let current = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL", QUOTE_COLUMNS))
+        .bind(&id)
+        .fetch_optional(&state.pool).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(|row| quote_from_row(&row))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let was_verified = current.source.as_ref().is_some_and(|source| source.verified);
+    let content = history::QuoteContent {
+        quote: payload.quote.unwrap_or(current.quote),
+        author: match payload.author {
+            Some(author) if author.trim().is_empty() => None,
+            Some(author) => Some(author),
+            None => current.author,
+        },
+        tags: payload.tags.map(|tags| normalize_tags(&tags)).unwrap_or(current.tags),
+    };
+
+    if
+        content.quote.trim().is_empty() ||
+        contains_inappropriate_content(&content.quote) ||
+        content.author.as_ref().is_some_and(|a| contains_inappropriate_content(a)) ||
+        content.tags.iter().any(|t| contains_inappropriate_content(t))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let source = match payload.source {
+        Some(source) => Some(citation::normalize_source(source)?),
+        None => None,
+    };
+    let language = match &payload.language {
+        Some(tag) => Some(language::resolve_language(Some(tag), &content.quote)?),
+        None => None,
+    };
+
+    let editor = payload.editor.unwrap_or_else(|| "admin".to_string());
+    let result = async {
+        let Some(mut quote) = history::apply_edit(&state.pool, &id, content, &editor).await? else {
+            return Ok(None);
+        };
+        if let Some(source) = source {
+            citation::save_source(&state.pool, &id, source.as_ref()).await?;
+            quote.source = source;
+        }
+        if let Some(language) = language {
+            language::save_language(&state.pool, &id, language.as_deref()).await?;
+            quote.language = language;
+        }
+        Ok::<_, sqlx::Error>(Some(quote))
+    }.await;
+
+    match result {
+        Ok(Some(quote)) => {
+            state.random.invalidate().await;
+            state.daily.forget_quote(&id).await;
+            state.events.publish(events::QuoteEvent::Updated { quote: quote.clone() });
+            if !was_verified && quote.source.as_ref().is_some_and(|source| source.verified) {
+                state.events.publish(events::QuoteEvent::Approved { quote: quote.clone() });
+            }
+            state.live.moderation(ModerationAction::Edited, Some(&quote.id), Some(&editor));
+            Ok(Json(quote))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_quotes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QuoteQuery>
+) -> Result<(HeaderMap, Json<Vec<Quote>>), StatusCode> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+    let sort = params.sort.unwrap_or_default();
+    let cursor = params.cursor.as_deref().map(Cursor::decode).transpose()?;
+
+    let page_offset = params.page.unwrap_or(1).saturating_sub(1).saturating_mul(limit);
+    let (after, seed, offset) = match (sort, cursor) {
+        (QuoteSort::Random, Some(Cursor::Shuffled { seed, offset })) => (None, Some(seed), offset),
+        // Picked here rather than in `fetch_quotes` so the next cursor can
+        // carry it.
+        (QuoteSort::Random, None) => (None, Some(params.seed.unwrap_or_else(rand::random)), page_offset),
+        (QuoteSort::Random, Some(Cursor::After(_))) | (_, Some(Cursor::Shuffled { .. })) => {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        (_, Some(Cursor::After(key))) => (Some(key), None, 0),
+        (_, None) => (None, None, page_offset),
+    };
+
+    let languages = language::requested_languages(params.lang.as_deref(), &headers)?;
+    let filter = QuoteFilter::new(
+        params.search.as_deref(),
+        params.tag.as_deref(),
+        params.author.as_deref(),
+        languages
+    );
+
+    match fetch_quotes(&state.pool, &filter, sort, seed, after.as_ref(), limit, offset).await {
+        Ok(quotes) => {
+            let mut headers = HeaderMap::new();
+            if let Some(next) = Cursor::next(sort, &quotes, limit, offset, seed) {
+                if let Ok(value) = next.encode().parse() {
+                    headers.insert(pagination::NEXT_CURSOR, value);
+                }
+            }
+            Ok((headers, Json(quotes)))
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn fetch_quote(pool: &MySqlPool, id: &str) -> Result<Option<Quote>, sqlx::Error> {
+    let row = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL", QUOTE_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool).await?;
+    Ok(row.as_ref().map(quote_from_row))
+}
+
+async fn fetch_quotes(
+    pool: &MySqlPool,
+    filter: &QuoteFilter,
+    sort: QuoteSort,
+    seed: Option<u32>,
+    after: Option<&SortKey>,
+    limit: u64,
+    offset: u64
+) -> Result<Vec<Quote>, sqlx::Error> {
+    let mut conditions = filter.conditions();
+    let after = after.zip(pagination::after_condition(sort));
+    if let Some((_, condition)) = after {
+        conditions.push(condition.to_string());
+    }
+    let sql = format!(
+        "SELECT {} FROM quotes
+         WHERE {}
+         ORDER BY {}
+         LIMIT ? OFFSET ?",
+        QUOTE_COLUMNS,
+        conditions.join(" AND "),
+        sort.order_by()
+    );
+
+    let mut query = filter.bind(sqlx::query(&sql));
+    if let Some((key, _)) = after {
+        query = pagination::bind_after(query, sort, key);
+    }
+    if let QuoteSort::Random = sort {
+        query = query.bind(seed.unwrap_or_else(rand::random));
+    }
+    let rows = query.bind(limit).bind(offset).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(quote_from_row).collect())
+}
+
+async fn get_quote_by_id(
+    State(state): State<AppState>,
+    axum::extract::Path(id): axum::extract::Path<String>
+) -> Result<Json<Quote>, StatusCode> {
+    println!("Attempting to fetch quote with ID: {}", id);
+
+    let result = sqlx
+        ::query(&format!("SELECT {} FROM quotes WHERE id = ? AND deleted_at IS NULL", QUOTE_COLUMNS))
+        .bind(&id)
+        .fetch_optional(&state.pool).await;
+
+    match result {
+        Ok(Some(row)) => {
+            println!("Found quote: {}", id);
+            Ok(Json(quote_from_row(&row)))
+        }
+        Ok(None) => {
+            println!("Quote not found: {}", id);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    println!("Connecting to database...");
+    let pool = MySqlPool::connect(&database_url).await.expect("Failed to connect to MySQL");
+
+    println!("Creating/verifying database table...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS quotes (
+            id VARCHAR(36) PRIMARY KEY,
+            quote TEXT NOT NULL,
+            author VARCHAR(255),
+            like_count INT UNSIGNED NOT NULL DEFAULT 0,
+            source_title VARCHAR(255),
+            source_year SMALLINT,
+            source_url VARCHAR(2048),
+            source_page VARCHAR(50),
+            source_verified BOOLEAN NOT NULL DEFAULT FALSE,
+            language VARCHAR(35),
+            translation_of VARCHAR(36),
+            submission_id VARCHAR(64),
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            deleted_at TIMESTAMP NULL DEFAULT NULL,
+            INDEX idx_quotes_translation_of (translation_of),
+            UNIQUE INDEX idx_quotes_submission_id (submission_id)
+        )
+        "#
+    )
+        .execute(&pool).await
+        .expect("Failed to create quotes table");
+
+    add_column_if_missing(&pool, "quotes", "like_count", "INT UNSIGNED NOT NULL DEFAULT 0 AFTER author")
+        .await
+        .expect("Failed to add quotes.like_count");
+    add_column_if_missing(&pool, "quotes", "deleted_at", "TIMESTAMP NULL DEFAULT NULL")
+        .await
+        .expect("Failed to add quotes.deleted_at");
+    for (column, definition) in [
+        ("source_title", "VARCHAR(255) AFTER like_count"),
+        ("source_year", "SMALLINT AFTER source_title"),
+        ("source_url", "VARCHAR(2048) AFTER source_year"),
+        ("source_page", "VARCHAR(50) AFTER source_url"),
+        ("source_verified", "BOOLEAN NOT NULL DEFAULT FALSE AFTER source_page"),
+        ("language", "VARCHAR(35) AFTER source_verified"),
+        ("translation_of", "VARCHAR(36) AFTER language, ADD INDEX idx_quotes_translation_of (translation_of)"),
+        ("submission_id", "VARCHAR(64) AFTER translation_of, ADD UNIQUE INDEX idx_quotes_submission_id (submission_id)"),
+    ] {
+        add_column_if_missing(&pool, "quotes", column, definition)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to add quotes.{}: {}", column, e));
+    }
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS quote_tags (
+            quote_id VARCHAR(36) NOT NULL,
+            tag VARCHAR(50) NOT NULL,
+            PRIMARY KEY (quote_id, tag),
+            INDEX idx_quote_tags_tag (tag)
+        )
+        "#
+    )
+        .execute(&pool).await
+        .expect("Failed to create quote_tags table");
+
+    daily::create_table(&pool).await.expect("Failed to create daily_quotes table");
+    likes::create_table(&pool).await.expect("Failed to create quote_likes table");
+    collections::create_tables(&pool).await.expect("Failed to create collection tables");
+    history::create_table(&pool).await.expect("Failed to create quote_revisions table");
+    webhooks::create_tables(&pool).await.expect("Failed to create webhook tables");
+
+    let tagged = language::backfill_languages(&pool).await.expect("Failed to detect quote languages");
+    if tagged > 0 {
+        println!("Detected the language of {} existing quote(s)", tagged);
+    }
+
+    println!("Database table created/verified");
+
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers(Any)
+        .expose_headers([pagination::NEXT_CURSOR]);
+
+    let state = Arc::new(App {
+        pool,
+        daily: daily::DailyState::from_env(),
+        random: random::RandomPool::default(),
+        events: events::EventHub::default(),
+        live: ws::LiveHub::default(),
+    });
+
+    trash::spawn_purge_task(state.clone());
+    webhooks::spawn_dispatcher(state.clone());
+
+    let app = Router::new()
+        .route("/api/quotes/random", get(random::get_random_quote))
+        .route("/api/quotes/daily", get(daily::get_daily_quote))
+        .route("/api/quotes/stream", get(events::stream_quotes))
+        .route("/api/quotes/:id", get(get_quote_by_id).put(update_quote).delete(trash::delete_quote))
+        .route("/api/quotes/:id/cite", get(citation::cite_quote))
+        .route("/api/quotes/:id/card.svg", get(cards::card_svg))
+        .route("/api/quotes/:id/card.png", get(cards::card_png))
+        .route("/api/quotes/:id/translations", get(language::get_translations))
+        .route("/api/quotes/:id/history", get(history::get_history))
+        .route("/api/quotes/:id/history/diff", get(history::get_diff))
+        .route("/api/quotes/:id/like", post(likes::like_quote).delete(likes::unlike_quote))
+        .route("/api/quotes", get(get_quotes).post(create_quote))
+        .route("/api/ws", get(ws::connect))
+        .route(
+            "/api/collections",
+            get(collections::list_collections).post(collections::create_collection)
+        )
+        .route(
+            "/api/collections/:slug",
+            get(collections::get_collection)
+                .put(collections::update_collection)
+                .delete(collections::delete_collection)
+        )
+        .route(
+            "/api/collections/:slug/quotes",
+            post(collections::add_quote).put(collections::reorder_quotes)
+        )
+        .route("/api/collections/:slug/quotes/:quote_id", delete(collections::remove_quote))
+        .route("/api/admin/quotes/:id/revert/:revision", post(history::revert_to_revision))
+        .route("/api/admin/trash", get(trash::list_trash))
+        .route("/api/admin/trash/:id/restore", post(trash::restore_quote))
+        .route("/api/admin/webhooks", get(webhooks::list_webhooks).post(webhooks::create_webhook))
+        .route("/api/admin/webhooks/:id", put(webhooks::update_webhook).delete(webhooks::delete_webhook))
+        .route("/api/admin/webhooks/:id/test", post(webhooks::test_webhook))
+        .route("/api/admin/webhooks/:id/deliveries", get(webhooks::list_deliveries))
+        .route("/api/admin/webhooks/:id/deliveries/:delivery_id/redeliver", post(webhooks::redeliver))
+        .route("/api/admin/daily/:date", put(daily::pin_daily_quote).delete(daily::unpin_daily_quote))
+        .route("/share/:id", get(cards::share_page))
+        .route("/embed", get(embed::embed_page))
+        .route("/embed.js", get(embed::embed_script))
+        .route("/oembed", get(embed::oembed))
+        .route("/feeds/latest.rss", get(feeds::latest_rss))
+        .route("/feeds/latest.atom", get(feeds::latest_atom))
+        .route("/feeds/daily.rss", get(feeds::daily_rss))
+        .route("/feeds/daily.atom", get(feeds::daily_atom))
+        .layer(cors)
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+    println!("Server running on http://0.0.0.0:3000");
+
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+//! Cursor pagination for `GET /quotes`.
+//!
+//! A full page comes back with an `X-Next-Cursor` header; passing its value
+//! as `?cursor=` returns the page after it. For the sorted orders the cursor
+//! holds the sort key of the last quote sent, so the next page starts right
+//! after that quote even if others were added or deleted in between, where
+//! an offset would skip or repeat them. A shuffled order has no key to
+//! resume from, so its cursor holds the seed and the next offset instead.
+
+use axum::http::{ HeaderName, StatusCode };
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine };
+use chrono::{ DateTime, Utc };
+use serde::{ Deserialize, Serialize };
+use sqlx::{ mysql::MySqlArguments, query::Query as SqlQuery, MySql };
+
+use crate::{ Quote, QuoteSort };
+
+pub const NEXT_CURSOR: HeaderName = HeaderName::from_static("x-next-cursor");
+
+/// Where the previous page ended, by the columns quotes are ordered on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortKey {
+    created_at: DateTime<Utc>,
+    like_count: u32,
+    id: String,
+}
+
+impl SortKey {
+    fn of(quote: &Quote) -> SortKey {
+        SortKey {
+            created_at: quote.created_at,
+            like_count: quote.like_count,
+            id: quote.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Cursor {
+    After(SortKey),
+    Shuffled {
+        seed: u32,
+        offset: u64,
+    },
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(text: &str) -> Result<Cursor, StatusCode> {
+        URL_SAFE_NO_PAD.decode(text.trim())
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or(StatusCode::BAD_REQUEST)
+    }
+
+    /// The cursor for the page after `quotes`, or `None` when they did not
+    /// fill a page and so were the last. `offset` and `seed` are what the
+    /// page was fetched with.
+    pub fn next(sort: QuoteSort, quotes: &[Quote], limit: u64, offset: u64, seed: Option<u32>) -> Option<Cursor> {
+        if (quotes.len() as u64) < limit {
+            return None;
+        }
+        match (sort, seed) {
+            (QuoteSort::Random, Some(seed)) => Some(Cursor::Shuffled { seed, offset: offset + limit }),
+            (QuoteSort::Random, None) => None,
+            _ => quotes.last().map(|quote| Cursor::After(SortKey::of(quote))),
+        }
+    }
+}
+
+/// The `WHERE` condition selecting quotes after a `SortKey` in `sort` order,
+/// bound by `bind_after`. Ties on the sort columns are broken by id, as in
+/// `QuoteSort::order_by`. `None` for the shuffled order, which pages by
+/// offset.
+pub fn after_condition(sort: QuoteSort) -> Option<&'static str> {
+    match sort {
+        QuoteSort::Newest => Some("(created_at, id) < (?, ?)"),
+        QuoteSort::Oldest => Some("(created_at, id) > (?, ?)"),
+        QuoteSort::Popular => Some("(like_count, created_at, id) < (?, ?, ?)"),
+        QuoteSort::Random => None,
+    }
+}
+
+pub fn bind_after<'q>(
+    query: SqlQuery<'q, MySql, MySqlArguments>,
+    sort: QuoteSort,
+    key: &'q SortKey
+) -> SqlQuery<'q, MySql, MySqlArguments> {
+    match sort {
+        QuoteSort::Newest | QuoteSort::Oldest => query.bind(key.created_at).bind(&key.id),
+        QuoteSort::Popular => query.bind(key.like_count).bind(key.created_at).bind(&key.id),
+        QuoteSort::Random => query,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn quote(id: &str, like_count: u32, minute: u32) -> Quote {
+        let created_at = Utc.with_ymd_and_hms(2024, 5, 1, 12, minute, 0).unwrap();
+        Quote {
+            id: id.to_string(),
+            quote: "Text".to_string(),
+            author: None,
+            tags: Vec::new(),
+            like_count,
+            source: None,
+            language: None,
+            translation_of: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let cursors = [
+            Cursor::After(SortKey::of(&quote("7f3c", 12, 30))),
+            Cursor::Shuffled { seed: u32::MAX, offset: 40 },
+        ];
+        for cursor in cursors {
+            let encoded = cursor.encode();
+            assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{}", encoded);
+            assert_eq!(Cursor::decode(&encoded), Ok(cursor.clone()));
+            assert_eq!(Cursor::decode(&format!(" {}\n", encoded)), Ok(cursor));
+        }
+    }
+
+    #[test]
+    fn malformed_cursors_are_bad_requests() {
+        let not_a_cursor = URL_SAFE_NO_PAD.encode(br#"{"kind":"sideways"}"#);
+        for text in ["", "not base64!", "e30", not_a_cursor.as_str()] {
+            assert_eq!(Cursor::decode(text), Err(StatusCode::BAD_REQUEST), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn a_short_page_is_the_last() {
+        let quotes = [quote("a", 0, 1), quote("b", 0, 2)];
+        assert_eq!(Cursor::next(QuoteSort::Newest, &quotes, 3, 0, None), None);
+        assert_eq!(Cursor::next(QuoteSort::Random, &quotes, 3, 0, Some(9)), None);
+        assert_eq!(Cursor::next(QuoteSort::Newest, &[], 0, 0, None), None);
+    }
+
+    #[test]
+    fn sorted_pages_continue_after_their_last_quote() {
+        let quotes = [quote("a", 5, 1), quote("b", 3, 2)];
+        for sort in [QuoteSort::Newest, QuoteSort::Oldest, QuoteSort::Popular] {
+            assert_eq!(Cursor::next(sort, &quotes, 2, 0, None), Some(Cursor::After(SortKey::of(&quotes[1]))));
+        }
+    }
+
+    #[test]
+    fn shuffled_pages_continue_at_the_next_offset() {
+        let quotes = [quote("a", 0, 1), quote("b", 0, 2)];
+        assert_eq!(
+            Cursor::next(QuoteSort::Random, &quotes, 2, 4, Some(9)),
+            Some(Cursor::Shuffled { seed: 9, offset: 6 })
+        );
+        // Without a seed the order cannot be repeated, so there is no next page.
+        assert_eq!(Cursor::next(QuoteSort::Random, &quotes, 2, 4, None), None);
+    }
+
+    #[test]
+    fn shuffled_order_has_no_keyset_condition() {
+        assert_eq!(after_condition(QuoteSort::Random), None);
+        for sort in [QuoteSort::Newest, QuoteSort::Oldest, QuoteSort::Popular] {
+            assert!(after_condition(sort).is_some_and(|condition| condition.contains("id)")));
+        }
+    }
+}
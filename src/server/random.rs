@@ -0,0 +1,447 @@
+//! Random quote selection.
+//!
+//! Instead of `ORDER BY RAND()`, which sorts the whole table per request, the
+//! server keeps every quote id in memory along with author and tag indexes.
+//! A pick is a random position in one of those lists followed by a primary
+//! key lookup. The index is rebuilt from the database when it goes stale.
+//!
+//! Clients that pass `?session=<token>` draw from a per-session shuffled deck
+//! instead, so nothing repeats until every matching quote has been shown.
+
+use axum::{ extract::{ Query, State }, http::StatusCode, response::Json };
+use chrono::{ DateTime, Utc };
+use rand::{ seq::index, Rng };
+use serde::{ Deserialize, Serialize };
+use sqlx::{ mysql::MySqlPool, Row };
+use std::{ borrow::Cow, collections::{ HashMap, HashSet }, time::{ Duration, Instant } };
+use tokio::sync::{ Mutex, RwLock };
+
+use crate::{ quote_from_row, AppState, Quote, QUOTE_COLUMNS };
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const MAX_SESSIONS: usize = 10_000;
+const MAX_COUNT: usize = 50;
+/// Under `weight=recency` a brand-new quote is this many times as likely to be
+/// drawn as a very old one; the boost halves every `RECENCY_HALF_LIFE_DAYS`.
+const RECENCY_BOOST: f64 = 4.0;
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weighting {
+    Uniform,
+    Recency,
+    Popularity,
+}
+
+#[derive(Default)]
+struct RandomIndex {
+    ids: Vec<String>,
+    positions: HashMap<String, usize>,
+    created_at: Vec<DateTime<Utc>>,
+    like_counts: Vec<u32>,
+    by_author: HashMap<String, Vec<usize>>,
+    by_tag: HashMap<String, Vec<usize>>,
+    loaded_at: Option<Instant>,
+}
+
+impl RandomIndex {
+    fn push(&mut self, quote: IndexedQuote) {
+        let IndexedQuote { id, author, tags, like_count, created_at } = quote;
+        let position = self.ids.len();
+        self.positions.insert(id.clone(), position);
+        self.ids.push(id);
+        self.created_at.push(created_at);
+        self.like_counts.push(like_count);
+
+        if let Some(author) = author {
+            self.by_author.entry(author.trim().to_lowercase()).or_default().push(position);
+        }
+        for tag in tags {
+            self.by_tag.entry(tag.clone()).or_default().push(position);
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.loaded_at.is_none_or(|loaded_at| loaded_at.elapsed() > REFRESH_INTERVAL)
+    }
+
+    /// Positions matching every given filter. Only a filter on both author
+    /// and tag builds a new list; otherwise the index's own lists are used.
+    fn candidates(&self, author: Option<&str>, tag: Option<&str>) -> Candidates<'_> {
+        let by_author = author.map(|a| self.by_author.get(&a.trim().to_lowercase()).map_or(&[][..], Vec::as_slice));
+        let by_tag = tag.map(|t| self.by_tag.get(&t.trim().to_lowercase()).map_or(&[][..], Vec::as_slice));
+
+        match (by_author, by_tag) {
+            (None, None) => Candidates::All(self.ids.len()),
+            (Some(only), None) | (None, Some(only)) => Candidates::Listed(Cow::Borrowed(only)),
+            (Some(by_author), Some(by_tag)) => {
+                let tagged: HashSet<usize> = by_tag.iter().copied().collect();
+                Candidates::Listed(
+                    Cow::Owned(
+                        by_author
+                            .iter()
+                            .copied()
+                            .filter(|position| tagged.contains(position))
+                            .collect()
+                    )
+                )
+            }
+        }
+    }
+
+    fn weight(&self, position: usize, weighting: Weighting) -> f64 {
+        match weighting {
+            Weighting::Uniform => 1.0,
+            Weighting::Recency => {
+                let age_days = (Utc::now() - self.created_at[position]).num_seconds().max(0) as f64 / 86_400.0;
+                1.0 + RECENCY_BOOST * (0.5f64).powf(age_days / RECENCY_HALF_LIFE_DAYS)
+            }
+            // Square root so a handful of runaway favourites cannot crowd
+            // out everything else.
+            Weighting::Popularity => 1.0 + (self.like_counts[position] as f64).sqrt(),
+        }
+    }
+
+    fn sample(&self, filter: &Filter, count: usize) -> Vec<String> {
+        let positions = self.candidates(filter.author.as_deref(), filter.tag.as_deref());
+        let amount = count.min(positions.len());
+        let mut rng = rand::thread_rng();
+
+        let picked = match filter.weighting {
+            Weighting::Uniform => index::sample(&mut rng, positions.len(), amount),
+            weighting =>
+                index
+                    ::sample_weighted(&mut rng, positions.len(), |i| self.weight(positions.get(i), weighting), amount)
+                    .unwrap_or_else(|_| index::sample(&mut rng, positions.len(), amount)),
+        };
+
+        picked
+            .iter()
+            .map(|i| self.ids[positions.get(i)].clone())
+            .collect()
+    }
+
+    /// Every matching quote in weighted random order (Efraimidis–Spirakis
+    /// keys), so heavier quotes tend to come up earlier in the deck.
+    fn shuffled_deck(&self, filter: &Filter) -> Vec<String> {
+        let positions = self.candidates(filter.author.as_deref(), filter.tag.as_deref());
+        let mut rng = rand::thread_rng();
+
+        let mut keyed: Vec<(f64, usize)> = positions
+            .iter()
+            .map(|position| {
+                let weight = self.weight(position, filter.weighting);
+                (rng.gen::<f64>().powf(1.0 / weight), position)
+            })
+            .collect();
+        // Decks are drawn from the back, so the highest keys go last.
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        keyed
+            .into_iter()
+            .map(|(_, position)| self.ids[position].clone())
+            .collect()
+    }
+}
+
+/// The positions a pick is drawn from.
+enum Candidates<'a> {
+    /// Every quote in the index, of which there are this many.
+    All(usize),
+    Listed(Cow<'a, [usize]>),
+}
+
+impl Candidates<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Candidates::All(len) => *len,
+            Candidates::Listed(positions) => positions.len(),
+        }
+    }
+
+    /// The index position of the `i`th candidate.
+    fn get(&self, i: usize) -> usize {
+        match self {
+            Candidates::All(_) => i,
+            Candidates::Listed(positions) => positions[i],
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+}
+
+struct IndexedQuote<'a> {
+    id: String,
+    author: Option<&'a str>,
+    tags: &'a [String],
+    like_count: u32,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Filter {
+    author: Option<String>,
+    tag: Option<String>,
+    weighting: Weighting,
+}
+
+struct Session {
+    deck: Vec<String>,
+    last: Option<String>,
+    touched_at: Instant,
+}
+
+#[derive(Default)]
+pub struct RandomPool {
+    index: RwLock<RandomIndex>,
+    sessions: Mutex<HashMap<(String, Filter), Session>>,
+}
+
+impl RandomPool {
+    async fn reload(&self, pool: &MySqlPool) -> Result<(), sqlx::Error> {
+        let rows = sqlx
+            ::query(
+                "SELECT id, author, like_count, created_at,
+                 (SELECT GROUP_CONCAT(tag) FROM quote_tags WHERE quote_tags.quote_id = quotes.id) AS tags
+                 FROM quotes WHERE deleted_at IS NULL AND translation_of IS NULL"
+            )
+            .fetch_all(pool).await?;
+
+        let mut index = RandomIndex::default();
+        for row in rows {
+            let author: Option<String> = row.get("author");
+            let tags: Option<String> = row.get("tags");
+            let tags: Vec<String> = tags
+                .map(|tags| tags.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            index.push(IndexedQuote {
+                id: row.get("id"),
+                author: author.as_deref(),
+                tags: &tags,
+                like_count: row.get("like_count"),
+                created_at: row.get("created_at"),
+            });
+        }
+        index.loaded_at = Some(Instant::now());
+
+        *self.index.write().await = index;
+        Ok(())
+    }
+
+    async fn ensure_fresh(&self, pool: &MySqlPool) -> Result<(), sqlx::Error> {
+        if self.index.read().await.is_stale() {
+            self.reload(pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn sample(&self, pool: &MySqlPool, filter: &Filter, count: usize) -> Result<Vec<String>, sqlx::Error> {
+        self.ensure_fresh(pool).await?;
+        Ok(self.index.read().await.sample(filter, count))
+    }
+
+    /// Draws the next `count` quotes from the session's deck, reshuffling when
+    /// it runs out. A reshuffle never starts with the quote that ended the
+    /// previous pass, and one response never contains the same quote twice.
+    async fn draw(
+        &self,
+        pool: &MySqlPool,
+        token: &str,
+        filter: &Filter,
+        count: usize
+    ) -> Result<Vec<String>, sqlx::Error> {
+        self.ensure_fresh(pool).await?;
+        let index = self.index.read().await;
+        let mut sessions = self.sessions.lock().await;
+
+        sessions.retain(|_, session| session.touched_at.elapsed() < SESSION_IDLE_TIMEOUT);
+        let key = (token.to_string(), filter.clone());
+        if !sessions.contains_key(&key) && sessions.len() >= MAX_SESSIONS {
+            return Ok(index.sample(filter, count));
+        }
+
+        let session = sessions.entry(key).or_insert_with(|| Session {
+            deck: Vec::new(),
+            last: None,
+            touched_at: Instant::now(),
+        });
+        session.touched_at = Instant::now();
+
+        let mut drawn: Vec<String> = Vec::new();
+        while drawn.len() < count {
+            if session.deck.is_empty() {
+                let mut deck: Vec<String> = index
+                    .shuffled_deck(filter)
+                    .into_iter()
+                    .filter(|id| !drawn.contains(id))
+                    .collect();
+                if deck.is_empty() {
+                    break;
+                }
+                if deck.len() > 1 && deck.last() == session.last.as_ref() {
+                    let repeat = deck.pop().unwrap();
+                    deck.insert(0, repeat);
+                }
+                session.deck = deck;
+            }
+
+            if let Some(id) = session.deck.pop() {
+                session.last = Some(id.clone());
+                drawn.push(id);
+            }
+        }
+
+        Ok(drawn)
+    }
+
+    /// One uniformly random quote matching the filters, for pages that show
+    /// a random quote without going through `/random`.
+    pub async fn pick(
+        &self,
+        pool: &MySqlPool,
+        author: Option<&str>,
+        tag: Option<&str>
+    ) -> Result<Option<Quote>, sqlx::Error> {
+        let filter = Filter {
+            author: author.map(str::to_string),
+            tag: tag.map(str::to_string),
+            weighting: Weighting::Uniform,
+        };
+        let ids = self.sample(pool, &filter, 1).await?;
+        let quote = fetch_by_ids(pool, &ids).await?.pop();
+        if quote.is_none() && !ids.is_empty() {
+            self.invalidate().await;
+        }
+        Ok(quote)
+    }
+
+    /// Adds a freshly created quote without waiting for the next rebuild.
+    /// Open session decks pick it up on their next pass.
+    pub async fn insert(&self, quote: &Quote) {
+        let mut index = self.index.write().await;
+        if index.loaded_at.is_some() {
+            index.push(IndexedQuote {
+                id: quote.id.clone(),
+                author: quote.author.as_deref(),
+                tags: &quote.tags,
+                like_count: quote.like_count,
+                created_at: quote.created_at,
+            });
+        }
+    }
+
+    /// Keeps popularity weighting current between rebuilds.
+    pub async fn set_like_count(&self, id: &str, like_count: u32) {
+        let mut index = self.index.write().await;
+        if let Some(&position) = index.positions.get(id) {
+            index.like_counts[position] = like_count;
+        }
+    }
+
+    /// Forces a rebuild on the next pick, e.g. after quotes were removed.
+    pub async fn invalidate(&self) {
+        self.index.write().await.loaded_at = None;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RandomQuery {
+    count: Option<usize>,
+    tag: Option<String>,
+    author: Option<String>,
+    session: Option<String>,
+    weight: Option<Weighting>,
+}
+
+/// A plain `/random` keeps returning a single quote; `?count=N` returns a list.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum RandomQuotes {
+    One(Box<Quote>),
+    Many(Vec<Quote>),
+}
+
+fn is_valid_session_token(token: &str) -> bool {
+    (8..=64).contains(&token.len()) &&
+        token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+async fn fetch_by_ids(pool: &MySqlPool, ids: &[String]) -> Result<Vec<Quote>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let sql = format!("SELECT {} FROM quotes WHERE id IN ({}) AND deleted_at IS NULL", QUOTE_COLUMNS, placeholders);
+    let mut query = sqlx::query(&sql);
+    for id in ids {
+        query = query.bind(id);
+    }
+
+    let mut quotes: HashMap<String, Quote> = query
+        .fetch_all(pool).await?
+        .iter()
+        .map(|row| {
+            let quote = quote_from_row(row);
+            (quote.id.clone(), quote)
+        })
+        .collect();
+
+    // Keep the random order of the sample rather than the table order.
+    Ok(
+        ids
+            .iter()
+            .filter_map(|id| quotes.remove(id))
+            .collect()
+    )
+}
+
+pub async fn get_random_quote(
+    State(state): State<AppState>,
+    Query(params): Query<RandomQuery>
+) -> Result<Json<RandomQuotes>, StatusCode> {
+    let count = params.count.unwrap_or(1).clamp(1, MAX_COUNT);
+    if params.session.as_deref().is_some_and(|token| !is_valid_session_token(token)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let filter = Filter {
+        author: params.author.clone(),
+        tag: params.tag.clone(),
+        weighting: params.weight.unwrap_or(Weighting::Uniform),
+    };
+
+    let result = async {
+        let ids = match &params.session {
+            Some(token) => state.random.draw(&state.pool, token, &filter, count).await?,
+            None => state.random.sample(&state.pool, &filter, count).await?,
+        };
+        let quotes = fetch_by_ids(&state.pool, &ids).await?;
+
+        if quotes.len() < ids.len() {
+            // Something was removed behind the index's back.
+            state.random.invalidate().await;
+        }
+
+        Ok::<_, sqlx::Error>(quotes)
+    }.await;
+
+    match result {
+        Ok(quotes) if quotes.is_empty() => Err(StatusCode::NOT_FOUND),
+        Ok(mut quotes) => {
+            if params.count.is_some() {
+                Ok(Json(RandomQuotes::Many(quotes)))
+            } else {
+                Ok(Json(RandomQuotes::One(Box::new(quotes.remove(0)))))
+            }
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -0,0 +1,1115 @@
+//! Just enough TrueType and PNG to rasterize quote cards in pure Rust.
+//!
+//! Glyph outlines are read straight from a font's `glyf` table and filled by
+//! an anti-aliasing scanline rasterizer that accumulates signed coverage per
+//! pixel. The finished canvas is encoded as an RGB PNG with a small deflate
+//! implementation (fixed Huffman codes and LZ77 matching), which compresses
+//! the card's flat backgrounds well.
+
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Line(Point, Point),
+    Quad(Point, Point, Point),
+}
+
+/// A column-major 2x2 matrix plus offset, as used by composite glyphs.
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    xx: f32,
+    xy: f32,
+    yx: f32,
+    yy: f32,
+    dx: f32,
+    dy: f32,
+}
+
+impl Transform {
+    const IDENTITY: Transform = Transform { xx: 1.0, xy: 0.0, yx: 0.0, yy: 1.0, dx: 0.0, dy: 0.0 };
+
+    fn apply(&self, x: f32, y: f32) -> Point {
+        Point {
+            x: self.xx * x + self.yx * y + self.dx,
+            y: self.xy * x + self.yy * y + self.dy,
+        }
+    }
+
+    /// `self` applied after `inner`.
+    fn then(&self, inner: &Transform) -> Transform {
+        let origin = self.apply(inner.dx, inner.dy);
+        Transform {
+            xx: self.xx * inner.xx + self.yx * inner.xy,
+            xy: self.xy * inner.xx + self.yy * inner.xy,
+            yx: self.xx * inner.yx + self.yx * inner.yy,
+            yy: self.xy * inner.yx + self.yy * inner.yy,
+            dx: origin.x,
+            dy: origin.y,
+        }
+    }
+}
+
+/// Composite glyphs may nest; real fonts stay well below this.
+const MAX_COMPOSITE_DEPTH: u32 = 8;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// A parsed TrueType font. Only the tables needed to map characters to
+/// outlines and advances are read.
+pub struct Font {
+    data: &'static [u8],
+    units_per_em: f32,
+    ascent: f32,
+    cmap: usize,
+    cmap_format: u16,
+    hmtx: usize,
+    horizontal_metrics: u16,
+    loca: usize,
+    loca_long: bool,
+    glyf: usize,
+}
+
+impl Font {
+    pub fn parse(data: &'static [u8]) -> Option<Font> {
+        let table = |tag: &[u8; 4]| -> Option<usize> {
+            let count = read_u16(data, 4)? as usize;
+            (0..count).find_map(|i| {
+                let record = 12 + 16 * i;
+                (data.get(record..record + 4)? == tag).then(|| read_u32(data, record + 8))?
+            }).map(|offset| offset as usize)
+        };
+
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let cmap_table = table(b"cmap")?;
+
+        // Prefer the full Unicode subtable, then the BMP one.
+        let subtables = read_u16(data, cmap_table + 2)? as usize;
+        let mut cmap = None;
+        for i in 0..subtables {
+            let record = cmap_table + 4 + 8 * i;
+            let platform = read_u16(data, record)?;
+            let encoding = read_u16(data, record + 2)?;
+            let offset = cmap_table + read_u32(data, record + 4)? as usize;
+            let format = read_u16(data, offset)?;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            match format {
+                12 if unicode => {
+                    cmap = Some((offset, format));
+                    break;
+                }
+                4 if unicode && cmap.is_none() => {
+                    cmap = Some((offset, format));
+                }
+                _ => {}
+            }
+        }
+        let (cmap, cmap_format) = cmap?;
+
+        Some(Font {
+            data,
+            units_per_em: read_u16(data, head + 18)? as f32,
+            ascent: read_i16(data, hhea + 4)? as f32,
+            cmap,
+            cmap_format,
+            hmtx: table(b"hmtx")?,
+            horizontal_metrics: read_u16(data, hhea + 34)?,
+            loca: table(b"loca")?,
+            loca_long: read_i16(data, head + 50)? == 1,
+            glyf: table(b"glyf")?,
+        })
+    }
+
+    /// Glyph 0 (the "missing" box) for characters the font lacks.
+    fn glyph_index(&self, c: char) -> u16 {
+        let code = c as u32;
+        let data = self.data;
+        let lookup = || -> Option<u16> {
+            if self.cmap_format == 12 {
+                let groups = read_u32(data, self.cmap + 12)? as usize;
+                return (0..groups).find_map(|i| {
+                    let group = self.cmap + 16 + 12 * i;
+                    let start = read_u32(data, group)?;
+                    let end = read_u32(data, group + 4)?;
+                    (start..=end)
+                        .contains(&code)
+                        .then(|| read_u32(data, group + 8))?
+                        .map(|first| (first + code - start) as u16)
+                });
+            }
+
+            if code > 0xffff {
+                return None;
+            }
+            let code = code as u16;
+            let segments = (read_u16(data, self.cmap + 6)? / 2) as usize;
+            let ends = self.cmap + 14;
+            let starts = ends + 2 * segments + 2;
+            let deltas = starts + 2 * segments;
+            let range_offsets = deltas + 2 * segments;
+            for i in 0..segments {
+                if read_u16(data, ends + 2 * i)? < code {
+                    continue;
+                }
+                let start = read_u16(data, starts + 2 * i)?;
+                if start > code {
+                    return None;
+                }
+                let delta = read_u16(data, deltas + 2 * i)?;
+                let range_offset = read_u16(data, range_offsets + 2 * i)? as usize;
+                if range_offset == 0 {
+                    return Some(code.wrapping_add(delta));
+                }
+                let address = range_offsets + 2 * i + range_offset + 2 * (code - start) as usize;
+                let glyph = read_u16(data, address)?;
+                return Some(if glyph == 0 { 0 } else { glyph.wrapping_add(delta) });
+            }
+            None
+        };
+        lookup().unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> f32 {
+        let index = glyph.min(self.horizontal_metrics.saturating_sub(1)) as usize;
+        read_u16(self.data, self.hmtx + 4 * index).unwrap_or(0) as f32
+    }
+
+    /// Width of `text` in pixels at `size` pixels per em.
+    pub fn measure(&self, text: &str, size: f32) -> f32 {
+        let scale = size / self.units_per_em;
+        text.chars().map(|c| self.advance(self.glyph_index(c)) * scale).sum()
+    }
+
+    /// Distance from the top of a line to its baseline, in pixels.
+    pub fn ascent(&self, size: f32) -> f32 {
+        self.ascent * size / self.units_per_em
+    }
+
+    fn glyph_range(&self, glyph: u16) -> Option<(usize, usize)> {
+        let glyph = glyph as usize;
+        let (start, end) = if self.loca_long {
+            (read_u32(self.data, self.loca + 4 * glyph)?, read_u32(self.data, self.loca + 4 * glyph + 4)?)
+        } else {
+            (
+                (read_u16(self.data, self.loca + 2 * glyph)? as u32) * 2,
+                (read_u16(self.data, self.loca + 2 * glyph + 2)? as u32) * 2,
+            )
+        };
+        (end > start).then_some((self.glyf + start as usize, self.glyf + end as usize))
+    }
+
+    /// The outline of `glyph` in font units, y pointing up. Empty for glyphs
+    /// without contours, such as the space.
+    fn outline(&self, glyph: u16, transform: &Transform, depth: u32, segments: &mut Vec<Segment>) {
+        let Some((start, _)) = self.glyph_range(glyph) else {
+            return;
+        };
+        let Some(contours) = read_i16(self.data, start) else {
+            return;
+        };
+        if contours >= 0 {
+            self.simple_outline(start, contours as usize, transform, segments);
+        } else if depth < MAX_COMPOSITE_DEPTH {
+            self.composite_outline(start, transform, depth, segments);
+        }
+    }
+
+    fn simple_outline(
+        &self,
+        start: usize,
+        contours: usize,
+        transform: &Transform,
+        segments: &mut Vec<Segment>
+    ) -> Option<()> {
+        const ON_CURVE: u8 = 0x01;
+        const X_SHORT: u8 = 0x02;
+        const Y_SHORT: u8 = 0x04;
+        const REPEAT: u8 = 0x08;
+        const X_SAME_OR_POSITIVE: u8 = 0x10;
+        const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+        let data = self.data;
+        let ends: Vec<usize> = (0..contours)
+            .map(|i| read_u16(data, start + 10 + 2 * i).map(|end| end as usize))
+            .collect::<Option<_>>()?;
+        let count = ends.last().map_or(0, |end| end + 1);
+        let instructions = read_u16(data, start + 10 + 2 * contours)? as usize;
+        let mut offset = start + 12 + 2 * contours + instructions;
+
+        let mut flags = Vec::with_capacity(count);
+        while flags.len() < count {
+            let flag = *data.get(offset)?;
+            offset += 1;
+            flags.push(flag);
+            if flag & REPEAT != 0 {
+                let repeats = *data.get(offset)?;
+                offset += 1;
+                flags.extend(std::iter::repeat_n(flag, repeats as usize));
+            }
+        }
+        flags.truncate(count);
+
+        let mut read_coordinates = |short: u8, same_or_positive: u8| -> Option<Vec<f32>> {
+            let mut value = 0i32;
+            let mut values = Vec::with_capacity(count);
+            for flag in &flags {
+                if flag & short != 0 {
+                    let delta = *data.get(offset)? as i32;
+                    offset += 1;
+                    value += if flag & same_or_positive != 0 { delta } else { -delta };
+                } else if flag & same_or_positive == 0 {
+                    value += read_i16(data, offset)? as i32;
+                    offset += 2;
+                }
+                values.push(value as f32);
+            }
+            Some(values)
+        };
+        let xs = read_coordinates(X_SHORT, X_SAME_OR_POSITIVE)?;
+        let ys = read_coordinates(Y_SHORT, Y_SAME_OR_POSITIVE)?;
+
+        let mut first = 0;
+        for end in ends {
+            if end < first || end >= count {
+                return None;
+            }
+            let points: Vec<(Point, bool)> = (first..=end)
+                .map(|i| (transform.apply(xs[i], ys[i]), flags[i] & ON_CURVE != 0))
+                .collect();
+            contour_segments(&points, segments);
+            first = end + 1;
+        }
+        Some(())
+    }
+
+    fn composite_outline(
+        &self,
+        start: usize,
+        transform: &Transform,
+        depth: u32,
+        segments: &mut Vec<Segment>
+    ) -> Option<()> {
+        const ARGS_ARE_WORDS: u16 = 0x0001;
+        const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+        const HAVE_SCALE: u16 = 0x0008;
+        const MORE_COMPONENTS: u16 = 0x0020;
+        const HAVE_XY_SCALE: u16 = 0x0040;
+        const HAVE_TWO_BY_TWO: u16 = 0x0080;
+
+        let data = self.data;
+        let f2dot14 = |offset: usize| read_i16(data, offset).map(|value| value as f32 / 16384.0);
+        let mut offset = start + 10;
+        loop {
+            let flags = read_u16(data, offset)?;
+            let glyph = read_u16(data, offset + 2)?;
+            offset += 4;
+
+            let (dx, dy) = if flags & ARGS_ARE_WORDS != 0 {
+                let args = (read_i16(data, offset)? as f32, read_i16(data, offset + 2)? as f32);
+                offset += 4;
+                args
+            } else {
+                let args = (*data.get(offset)? as i8 as f32, *data.get(offset + 1)? as i8 as f32);
+                offset += 2;
+                args
+            };
+            // Point-matched components are rare in text fonts; they are
+            // placed at the origin rather than dropped.
+            let (dx, dy) = if flags & ARGS_ARE_XY_VALUES != 0 { (dx, dy) } else { (0.0, 0.0) };
+
+            let mut component = Transform { dx, dy, ..Transform::IDENTITY };
+            if flags & HAVE_SCALE != 0 {
+                let scale = f2dot14(offset)?;
+                component.xx = scale;
+                component.yy = scale;
+                offset += 2;
+            } else if flags & HAVE_XY_SCALE != 0 {
+                component.xx = f2dot14(offset)?;
+                component.yy = f2dot14(offset + 2)?;
+                offset += 4;
+            } else if flags & HAVE_TWO_BY_TWO != 0 {
+                component.xx = f2dot14(offset)?;
+                component.xy = f2dot14(offset + 2)?;
+                component.yx = f2dot14(offset + 4)?;
+                component.yy = f2dot14(offset + 6)?;
+                offset += 8;
+            }
+
+            self.outline(glyph, &transform.then(&component), depth + 1, segments);
+            if flags & MORE_COMPONENTS == 0 {
+                return Some(());
+            }
+        }
+    }
+}
+
+fn midpoint(a: Point, b: Point) -> Point {
+    Point { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0 }
+}
+
+/// Turns one closed TrueType contour into lines and quadratic curves. Two
+/// off-curve points in a row imply an on-curve point halfway between them.
+fn contour_segments(points: &[(Point, bool)], segments: &mut Vec<Segment>) {
+    let Some(&(first, first_on)) = points.first() else {
+        return;
+    };
+    let (last, last_on) = points[points.len() - 1];
+    let start = match (first_on, last_on) {
+        (true, _) => first,
+        (false, true) => last,
+        (false, false) => midpoint(last, first),
+    };
+
+    let rest = match (first_on, last_on) {
+        (true, _) => &points[1..],
+        (false, true) => &points[..points.len() - 1],
+        (false, false) => points,
+    };
+
+    let mut current = start;
+    let mut control: Option<Point> = None;
+    for &(point, on_curve) in rest.iter().chain(std::iter::once(&(start, true))) {
+        match (control, on_curve) {
+            (None, true) => {
+                segments.push(Segment::Line(current, point));
+                current = point;
+            }
+            (None, false) => control = Some(point),
+            (Some(c), true) => {
+                segments.push(Segment::Quad(current, c, point));
+                current = point;
+                control = None;
+            }
+            (Some(c), false) => {
+                let implied = midpoint(c, point);
+                segments.push(Segment::Quad(current, c, implied));
+                current = implied;
+                control = Some(point);
+            }
+        }
+    }
+}
+
+/// Signed-area coverage accumulator for one glyph.
+struct Raster {
+    width: usize,
+    height: usize,
+    cells: Vec<f32>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Raster {
+        Raster { width, height, cells: vec![0.0; width * height + 4] }
+    }
+
+    fn line(&mut self, p0: Point, p1: Point) {
+        if (p0.y - p1.y).abs() <= f32::EPSILON {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y < p1.y { (1.0, p0, p1) } else { (-1.0, p1, p0) };
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let mut x = p0.x;
+        if p0.y < 0.0 {
+            x -= p0.y * dxdy;
+        }
+
+        for y in (p0.y.max(0.0) as usize)..self.height.min(p1.y.ceil() as usize) {
+            let row = y * self.width;
+            let dy = ((y + 1) as f32).min(p1.y) - (y as f32).max(p0.y);
+            let x_next = x + dxdy * dy;
+            let d = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor.max(0.0) as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil.max(0.0) as usize;
+
+            if x1i <= x0i + 1 {
+                let xm = 0.5 * (x + x_next) - x0_floor;
+                self.cells[row + x0i] += d - d * xm;
+                self.cells[row + x0i + 1] += d * xm;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0f = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+                let x1f = x1 - x1_ceil + 1.0;
+                let am = 0.5 * s * x1f * x1f;
+                self.cells[row + x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    self.cells[row + x0i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    self.cells[row + x0i + 1] += d * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.cells[row + xi] += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.cells[row + x1i - 1] += d * (1.0 - a2 - am);
+                }
+                self.cells[row + x1i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+
+    fn quad(&mut self, p0: Point, c: Point, p1: Point) {
+        let dx = p0.x - 2.0 * c.x + p1.x;
+        let dy = p0.y - 2.0 * c.y + p1.y;
+        let deviation = dx * dx + dy * dy;
+        if deviation < 0.333 {
+            self.line(p0, p1);
+            return;
+        }
+        let steps = 1 + (3.0 * deviation).sqrt().sqrt().floor() as usize;
+        let mut previous = p0;
+        for i in 1..=steps {
+            let t = i as f32 / steps as f32;
+            let u = 1.0 - t;
+            let point = Point {
+                x: u * u * p0.x + 2.0 * u * t * c.x + t * t * p1.x,
+                y: u * u * p0.y + 2.0 * u * t * c.y + t * t * p1.y,
+            };
+            self.line(previous, point);
+            previous = point;
+        }
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+/// An RGB image that shapes and text are blended onto.
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas { width, height, pixels: vec![0; width * height * 3] }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Rgb, alpha: f32) {
+        if x >= self.width || y >= self.height || alpha <= 0.0 {
+            return;
+        }
+        let alpha = alpha.min(1.0);
+        let index = (y * self.width + x) * 3;
+        for (pixel, target) in self.pixels[index..index + 3].iter_mut().zip(color) {
+            let current = *pixel as f32;
+            *pixel = (current + (target as f32 - current) * alpha).round() as u8;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb, alpha: f32) {
+        let (x0, y0) = (x.max(0.0).round() as usize, y.max(0.0).round() as usize);
+        let (x1, y1) = ((x + width).max(0.0).round() as usize, (y + height).max(0.0).round() as usize);
+        for py in y0..y1.min(self.height) {
+            for px in x0..x1.min(self.width) {
+                self.blend(px, py, color, alpha);
+            }
+        }
+    }
+
+    /// Fills the whole canvas, blending from `top` to `bottom`.
+    pub fn vertical_gradient(&mut self, top: Rgb, bottom: Rgb) {
+        for y in 0..self.height {
+            let t = y as f32 / (self.height.max(2) - 1) as f32;
+            let color = lerp(top, bottom, t);
+            for x in 0..self.width {
+                self.blend(x, y, color, 1.0);
+            }
+        }
+    }
+
+    /// A rectangle whose color runs through `stops` from left to right.
+    pub fn horizontal_gradient(&mut self, x: f32, y: f32, width: f32, height: f32, stops: &[Rgb]) {
+        let (x0, y0) = (x.max(0.0).round() as usize, y.max(0.0).round() as usize);
+        let (x1, y1) = ((x + width).round() as usize, (y + height).round() as usize);
+        let span = (x1.saturating_sub(x0).max(2) - 1) as f32;
+        for px in x0..x1.min(self.width) {
+            let position = (px - x0) as f32 / span * (stops.len() - 1) as f32;
+            let index = (position.floor() as usize).min(stops.len().saturating_sub(2));
+            let color = lerp(stops[index], stops[(index + 1).min(stops.len() - 1)], position - index as f32);
+            for py in y0..y1.min(self.height) {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Draws `text` with its baseline at `baseline` and returns its width.
+    pub fn draw_text(&mut self, font: &Font, text: &str, x: f32, baseline: f32, size: f32, color: Rgb) -> f32 {
+        let scale = size / font.units_per_em;
+        let to_pixels = Transform { xx: scale, yy: -scale, dx: 0.0, dy: baseline, ..Transform::IDENTITY };
+        let mut pen = x;
+
+        for c in text.chars() {
+            let glyph = font.glyph_index(c);
+            let mut segments = Vec::new();
+            font.outline(glyph, &Transform { dx: pen, ..to_pixels }, 0, &mut segments);
+            self.fill_outline(&segments, color);
+            pen += font.advance(glyph) * scale;
+        }
+        pen - x
+    }
+
+    fn fill_outline(&mut self, segments: &[Segment], color: Rgb) {
+        let points = segments.iter().flat_map(|segment| match *segment {
+            Segment::Line(a, b) => [a, b, b],
+            Segment::Quad(a, c, b) => [a, c, b],
+        });
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for point in points {
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+        if min_x > max_x || max_x < 0.0 || max_y < 0.0 {
+            return;
+        }
+
+        let (left, top) = (min_x.floor(), min_y.floor());
+        let width = (max_x.ceil() - left) as usize + 2;
+        let height = (max_y.ceil() - top) as usize + 1;
+        let local = |p: Point| Point { x: p.x - left, y: p.y - top };
+
+        let mut raster = Raster::new(width, height);
+        for segment in segments {
+            match *segment {
+                Segment::Line(a, b) => raster.line(local(a), local(b)),
+                Segment::Quad(a, c, b) => raster.quad(local(a), local(c), local(b)),
+            }
+        }
+
+        let mut coverage = 0.0;
+        for (i, cell) in raster.cells.iter().take(width * height).enumerate() {
+            coverage += cell;
+            let (x, y) = (left + (i % width) as f32, top + (i / width) as f32);
+            if x >= 0.0 && y >= 0.0 {
+                self.blend(x as usize, y as usize, color, coverage.abs());
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width * 3) {
+            // Filter type 0: rows are stored as-is.
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolor, default compression, filter and
+        // no interlacing.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn lerp(from: Rgb, to: Rgb, t: f32) -> Rgb {
+    let channel = |i: usize| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t.clamp(0.0, 1.0)).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Writes `count` bits of `value`, least significant first.
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u32; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const DISTANCE_BASE: [u32; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const WINDOW: usize = 32768;
+const HASH_BITS: u32 = 15;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+
+/// Writes a literal/length symbol with the fixed Huffman code of RFC 1951.
+fn fixed_literal(out: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => out.code(0x30 + symbol, 8),
+        144..=255 => out.code(0x190 + symbol - 144, 9),
+        256..=279 => out.code(symbol - 256, 7),
+        _ => out.code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(out: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASE.iter().rposition(|&base| base as usize <= length).unwrap_or(0);
+    let extra = match index {
+        0..=7 | 28 => 0,
+        _ => (index as u32 - 4) / 4,
+    };
+    fixed_literal(out, 257 + index as u32);
+    out.bits(length as u32 - LENGTH_BASE[index], extra);
+
+    let index = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap_or(0);
+    let extra = if index < 4 { 0 } else { index as u32 / 2 - 1 };
+    out.code(index as u32, 5);
+    out.bits(distance as u32 - DISTANCE_BASE[index], extra);
+}
+
+/// Recent positions by the hash of the three bytes starting there.
+struct MatchTable {
+    head: Vec<usize>,
+    previous: Vec<usize>,
+}
+
+impl MatchTable {
+    fn hash(data: &[u8], i: usize) -> usize {
+        let value = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+        (value.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    }
+
+    fn insert(&mut self, data: &[u8], i: usize) {
+        if i + 3 <= data.len() {
+            let h = MatchTable::hash(data, i);
+            self.previous[i % WINDOW] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    /// The longest earlier match for the bytes at `i`, as (length, distance).
+    fn longest_match(&self, data: &[u8], i: usize) -> (usize, usize) {
+        let (mut best_length, mut best_distance) = (0, 0);
+        if i + 3 > data.len() {
+            return (0, 0);
+        }
+        let max_length = MAX_MATCH.min(data.len() - i);
+        let mut candidate = self.head[MatchTable::hash(data, i)];
+        let mut steps = 0;
+        while candidate != usize::MAX && i - candidate <= WINDOW && steps < MAX_CHAIN {
+            let length = (0..max_length).take_while(|&k| data[candidate + k] == data[i + k]).count();
+            if length > best_length {
+                (best_length, best_distance) = (length, i - candidate);
+                if length == max_length {
+                    break;
+                }
+            }
+            let next = self.previous[candidate % WINDOW];
+            // The slot was reused by a newer position: the chain ends.
+            if next == usize::MAX || next >= candidate {
+                break;
+            }
+            candidate = next;
+            steps += 1;
+        }
+        (best_length, best_distance)
+    }
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut table = MatchTable { head: vec![usize::MAX; 1 << HASH_BITS], previous: vec![usize::MAX; WINDOW] };
+
+    let mut out = BitWriter { bytes: vec![0x78, 0x01], buffer: 0, count: 0 };
+    // A single final block using the fixed Huffman codes.
+    out.bits(1, 1);
+    out.bits(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = table.longest_match(data, i);
+        if length >= 3 {
+            write_match(&mut out, length, distance);
+            for k in i..i + length {
+                table.insert(data, k);
+            }
+            i += length;
+        } else {
+            fixed_literal(&mut out, data[i] as u32);
+            table.insert(data, i);
+            i += 1;
+        }
+    }
+    fixed_literal(&mut out, 256);
+
+    let mut bytes = out.finish();
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u16(out: &mut Vec<u8>, value: u16) {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn push_i16(out: &mut Vec<u8>, value: i16) {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// A simple glyph with one all on-curve rectangular contour.
+    fn rectangle(x0: i16, y0: i16, x1: i16, y1: i16) -> Vec<u8> {
+        let mut glyph = Vec::new();
+        for value in [1, x0, y0, x1, y1] {
+            push_i16(&mut glyph, value);
+        }
+        // Last point index, then no instructions.
+        push_u16(&mut glyph, 3);
+        push_u16(&mut glyph, 0);
+        glyph.extend_from_slice(&[0x01; 4]);
+        for delta in [x0, x1 - x0, 0, x0 - x1] {
+            push_i16(&mut glyph, delta);
+        }
+        for delta in [y0, 0, y1 - y0, 0] {
+            push_i16(&mut glyph, delta);
+        }
+        glyph
+    }
+
+    /// Glyph 1 raised by 400 units, then glyph 1 scaled to half size.
+    fn composite() -> Vec<u8> {
+        let mut glyph = Vec::new();
+        for value in [-1, 50, 0, 400, 700] {
+            push_i16(&mut glyph, value);
+        }
+        // ARGS_ARE_WORDS | ARGS_ARE_XY_VALUES | MORE_COMPONENTS
+        push_u16(&mut glyph, 0x0023);
+        push_u16(&mut glyph, 1);
+        push_i16(&mut glyph, 0);
+        push_i16(&mut glyph, 400);
+        // ARGS_ARE_WORDS | ARGS_ARE_XY_VALUES | HAVE_SCALE
+        push_u16(&mut glyph, 0x000b);
+        push_u16(&mut glyph, 1);
+        push_i16(&mut glyph, 0);
+        push_i16(&mut glyph, 0);
+        push_i16(&mut glyph, 8192);
+        glyph
+    }
+
+    /// A format 4 subtable mapping ' ' to glyph 4 and '!' to nothing through
+    /// the glyph id array, and 'A'..='C' to glyphs 1..=3 by delta.
+    fn format_4_cmap() -> Vec<u8> {
+        // (start, end, delta, range offset); the range offset of the first
+        // segment points just past the offsets, at the glyph id array.
+        let segments: [(u16, u16, u16, u16); 3] = [
+            (0x20, 0x21, 0, 6),
+            (0x41, 0x43, 1u16.wrapping_sub(0x41), 0),
+            (0xffff, 0xffff, 1, 0),
+        ];
+        let mut subtable = Vec::new();
+        push_u16(&mut subtable, 4);
+        push_u16(&mut subtable, 16 + 8 * segments.len() as u16 + 4);
+        push_u16(&mut subtable, 0);
+        push_u16(&mut subtable, 2 * segments.len() as u16);
+        // The binary search hints, which lookups do not use.
+        subtable.extend_from_slice(&[0; 6]);
+        segments.iter().for_each(|segment| push_u16(&mut subtable, segment.1));
+        push_u16(&mut subtable, 0);
+        segments.iter().for_each(|segment| push_u16(&mut subtable, segment.0));
+        segments.iter().for_each(|segment| push_u16(&mut subtable, segment.2));
+        segments.iter().for_each(|segment| push_u16(&mut subtable, segment.3));
+        push_u16(&mut subtable, 4);
+        push_u16(&mut subtable, 0);
+
+        let mut cmap = Vec::new();
+        for value in [0, 1, 3, 1] {
+            push_u16(&mut cmap, value);
+        }
+        cmap.extend_from_slice(&12u32.to_be_bytes());
+        cmap.extend_from_slice(&subtable);
+        cmap
+    }
+
+    /// A font with a box as glyph 0, a rectangle as glyph 1, a composite of
+    /// it as glyph 2, a wider rectangle as glyph 3 and an empty glyph 4.
+    fn test_font() -> Font {
+        let glyphs = [
+            rectangle(0, 0, 500, 700),
+            rectangle(100, 0, 400, 300),
+            composite(),
+            rectangle(0, 0, 600, 300),
+            vec![],
+        ];
+        let mut glyf = Vec::new();
+        let mut loca = Vec::new();
+        for glyph in &glyphs {
+            push_u16(&mut loca, (glyf.len() / 2) as u16);
+            glyf.extend_from_slice(glyph);
+        }
+        push_u16(&mut loca, (glyf.len() / 2) as u16);
+
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[34..36].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+        let mut hmtx = Vec::new();
+        for advance in [500, 600, 600, 700, 300] {
+            push_u16(&mut hmtx, advance);
+            push_i16(&mut hmtx, 0);
+        }
+
+        let tables: [(&[u8; 4], Vec<u8>); 6] = [
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"cmap", format_4_cmap()),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"glyf", glyf),
+        ];
+        let mut font = vec![0, 1, 0, 0];
+        push_u16(&mut font, tables.len() as u16);
+        font.extend_from_slice(&[0; 6]);
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, table) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&[0; 4]);
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += table.len().next_multiple_of(4);
+        }
+        for (_, table) in &tables {
+            font.extend_from_slice(table);
+            font.resize(font.len().next_multiple_of(4), 0);
+        }
+
+        Font::parse(Vec::leak(font)).expect("test font parses")
+    }
+
+    fn bounds(segments: &[Segment]) -> (f32, f32, f32, f32) {
+        let mut bounds = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for segment in segments {
+            let points = match *segment {
+                Segment::Line(a, b) => vec![a, b],
+                Segment::Quad(a, c, b) => vec![a, c, b],
+            };
+            for point in points {
+                bounds = (bounds.0.min(point.x), bounds.1.min(point.y), bounds.2.max(point.x), bounds.3.max(point.y));
+            }
+        }
+        bounds
+    }
+
+    #[test]
+    fn format_4_cmap_maps_by_delta_and_glyph_array() {
+        let font = test_font();
+        assert_eq!(font.cmap_format, 4);
+        assert_eq!(font.glyph_index('A'), 1);
+        assert_eq!(font.glyph_index('B'), 2);
+        assert_eq!(font.glyph_index('C'), 3);
+        assert_eq!(font.glyph_index(' '), 4);
+        assert_eq!(font.glyph_index('!'), 0);
+    }
+
+    #[test]
+    fn characters_outside_the_cmap_use_the_missing_glyph() {
+        let font = test_font();
+        for c in ['@', 'D', '\u{e9}', '\u{1f600}'] {
+            assert_eq!(font.glyph_index(c), 0, "{:?}", c);
+        }
+        assert_eq!(font.measure("D!", 10.0), 10.0);
+
+        let mut canvas = Canvas::new(80, 100);
+        let width = canvas.draw_text(&font, "\u{1f600}", 10.0, 80.0, 100.0, [255, 255, 255]);
+        assert_eq!(width, 50.0);
+        // Glyph 0 is a box 50 by 70 pixels at this size.
+        let pixel = |x: usize, y: usize| &canvas.pixels[(y * 80 + x) * 3..(y * 80 + x) * 3 + 3];
+        assert_eq!(pixel(30, 50), [255, 255, 255]);
+        assert_eq!(pixel(30, 5), [0, 0, 0]);
+        assert_eq!(pixel(70, 50), [0, 0, 0]);
+    }
+
+    #[test]
+    fn composite_glyphs_transform_their_components() {
+        let font = test_font();
+        let mut segments = Vec::new();
+        font.outline(2, &Transform::IDENTITY, 0, &mut segments);
+
+        assert_eq!(segments.len(), 8);
+        assert!(segments.iter().all(|segment| matches!(segment, Segment::Line(..))));
+        let (raised, scaled) = segments.split_at(4);
+        assert_eq!(bounds(raised), (100.0, 400.0, 400.0, 700.0));
+        assert_eq!(bounds(scaled), (50.0, 0.0, 200.0, 150.0));
+    }
+
+    #[test]
+    fn composite_glyphs_stop_at_the_depth_limit() {
+        let font = test_font();
+        let mut segments = Vec::new();
+        font.outline(2, &Transform::IDENTITY, MAX_COMPOSITE_DEPTH, &mut segments);
+        assert!(segments.is_empty());
+    }
+
+    #[test]
+    fn empty_glyphs_have_no_outline() {
+        let font = test_font();
+        let mut segments = Vec::new();
+        font.outline(font.glyph_index(' '), &Transform::IDENTITY, 0, &mut segments);
+        assert!(segments.is_empty());
+        assert_eq!(font.measure(" ", 10.0), 3.0);
+    }
+
+    #[test]
+    fn bundled_font_maps_through_format_12() {
+        let font = Font::parse(include_bytes!("../../assets/fonts/DejaVuSansMono.ttf")).expect("bundled font parses");
+        assert_eq!(font.cmap_format, 12);
+        assert_ne!(font.glyph_index('A'), 0);
+        assert_ne!(font.glyph_index('\u{e9}'), font.glyph_index('e'));
+        assert_eq!(font.glyph_index('\u{10fffd}'), 0);
+    }
+
+    #[test]
+    fn crc32_matches_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn adler32_matches_known_values() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        // Long enough to need the periodic modulo.
+        assert_eq!(adler32(&[0xff; 100_000]), 0x149a_302c);
+    }
+
+    fn pseudo_random(length: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491u32;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zlib_round_trips_through_inflate() {
+        let period: Vec<u8> = pseudo_random(30_000);
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            b"a".to_vec(),
+            b"the quick brown fox jumps over the lazy dog".to_vec(),
+            vec![0; 100_000],
+            b"abcabcabd".repeat(5_000),
+            pseudo_random(70_000),
+            // Matches at distances near the window size.
+            period.iter().chain(&period).chain(&period).copied().collect(),
+        ];
+
+        for input in inputs {
+            let compressed = zlib(&input);
+            let inflated = miniz_oxide::inflate::decompress_to_vec_zlib(&compressed).expect("valid zlib stream");
+            assert_eq!(inflated, input, "{} bytes", input.len());
+        }
+        assert!(zlib(&[0; 100_000]).len() < 1_000);
+    }
+
+    #[test]
+    fn png_chunks_hold_the_canvas() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.fill_rect(0.0, 0.0, 3.0, 2.0, [10, 20, 30], 1.0);
+        canvas.fill_rect(1.0, 1.0, 1.0, 1.0, [200, 100, 0], 1.0);
+        let png = canvas.to_png();
+
+        assert_eq!(png[..8], [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while offset < png.len() {
+            let length = read_u32(&png, offset).unwrap() as usize;
+            let body = &png[offset + 4..offset + 8 + length];
+            assert_eq!(read_u32(&png, offset + 8 + length), Some(crc32(body)));
+            chunks.push((&body[..4], &body[4..]));
+            offset += 12 + length;
+        }
+        assert_eq!(offset, png.len());
+
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+        let raw = miniz_oxide::inflate::decompress_to_vec_zlib(chunks[1].1).expect("valid zlib stream");
+        assert_eq!(raw, [0, 10, 20, 30, 10, 20, 30, 10, 20, 30, 0, 10, 20, 30, 200, 100, 0, 10, 20, 30]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Soft delete.
+//!
+//! Deleting a quote only stamps `deleted_at`; every public query filters on
+//! `deleted_at IS NULL`. Admins can list and restore trashed quotes until a
+//! background task hard-deletes them after `TRASH_RETENTION_DAYS`.
+
+use axum::{ extract::{ Path, State }, http::{ HeaderMap, StatusCode }, response::Json };
+use chrono::{ DateTime, Duration, Utc };
+use serde::Serialize;
+use sqlx::{ mysql::MySqlPool, Row };
+use std::env;
+
+use crate::{ events::QuoteEvent, protocol::ModerationAction, quote_from_row, require_admin, AppState, Quote, QUOTE_COLUMNS };
+
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+#[derive(Debug, Serialize)]
+pub struct TrashedQuote {
+    #[serde(flatten)]
+    quote: Quote,
+    deleted_at: DateTime<Utc>,
+    purge_at: DateTime<Utc>,
+}
+
+fn retention() -> Duration {
+    let days = env
+        ::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(30);
+    Duration::days(days)
+}
+
+/// Tables that reference a quote and go with it when it is purged.
+const DEPENDENT_TABLES: &[&str] = &[
+    "quote_tags",
+    "quote_likes",
+    "quote_revisions",
+    "collection_quotes",
+    "daily_quotes",
+];
+
+async fn purge_expired(pool: &MySqlPool) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - retention();
+    let ids: Vec<String> = sqlx
+        ::query_scalar("SELECT id FROM quotes WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+        .bind(cutoff)
+        .fetch_all(pool).await?;
+
+    for id in &ids {
+        let mut tx = pool.begin().await?;
+        for table in DEPENDENT_TABLES {
+            sqlx::query(&format!("DELETE FROM {} WHERE quote_id = ?", table))
+                .bind(id)
+                .execute(&mut *tx).await?;
+        }
+        // Translations outlive their original as standalone quotes.
+        sqlx::query("UPDATE quotes SET translation_of = NULL, updated_at = updated_at WHERE translation_of = ?")
+            .bind(id)
+            .execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM quotes WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(&mut *tx).await?;
+        tx.commit().await?;
+    }
+
+    Ok(ids.len() as u64)
+}
+
+pub fn spawn_purge_task(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_expired(&state.pool).await {
+                Ok(0) => {}
+                Ok(purged) => println!("Purged {} quote(s) from trash", purged),
+                Err(e) => eprintln!("Database error while purging trash: {}", e),
+            }
+        }
+    });
+}
+
+pub async fn delete_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&headers)?;
+
+    let result = sqlx
+        ::query(
+            "UPDATE quotes SET deleted_at = CURRENT_TIMESTAMP, updated_at = updated_at
+             WHERE id = ? AND deleted_at IS NULL"
+        )
+        .bind(&id)
+        .execute(&state.pool).await;
+
+    match result {
+        Ok(done) if done.rows_affected() == 0 => Err(StatusCode::NOT_FOUND),
+        Ok(_) => {
+            state.random.invalidate().await;
+            state.daily.forget_quote(&id).await;
+            state.live.moderation(ModerationAction::Deleted, Some(&id), None);
+            state.events.publish(QuoteEvent::Deleted { id });
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn list_trash(
+    State(state): State<AppState>,
+    headers: HeaderMap
+) -> Result<Json<Vec<TrashedQuote>>, StatusCode> {
+    require_admin(&headers)?;
+
+    let result = sqlx
+        ::query(
+            &format!(
+                "SELECT {}, deleted_at FROM quotes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+                QUOTE_COLUMNS
+            )
+        )
+        .fetch_all(&state.pool).await;
+
+    match result {
+        Ok(rows) => {
+            let retention = retention();
+            Ok(
+                Json(
+                    rows
+                        .iter()
+                        .map(|row| {
+                            let deleted_at: DateTime<Utc> = row.get("deleted_at");
+                            TrashedQuote {
+                                quote: quote_from_row(row),
+                                deleted_at,
+                                purge_at: deleted_at + retention,
+                            }
+                        })
+                        .collect()
+                )
+            )
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+pub async fn restore_quote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<Json<Quote>, StatusCode> {
+    require_admin(&headers)?;
+
+    let result = async {
+        let restored = sqlx
+            ::query(
+                "UPDATE quotes SET deleted_at = NULL, updated_at = updated_at
+                 WHERE id = ? AND deleted_at IS NOT NULL"
+            )
+            .bind(&id)
+            .execute(&state.pool).await?
+            .rows_affected();
+        if restored == 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx
+            ::query(&format!("SELECT {} FROM quotes WHERE id = ?", QUOTE_COLUMNS))
+            .bind(&id)
+            .fetch_one(&state.pool).await?;
+        Ok::<_, sqlx::Error>(Some(quote_from_row(&row)))
+    }.await;
+
+    match result {
+        Ok(Some(quote)) => {
+            state.random.invalidate().await;
+            // It may be pinned for today, in place of a stand-in.
+            state.daily.invalidate(state.daily.today()).await;
+            // To clients a restored quote is simply a new one.
+            state.events.publish(QuoteEvent::Created { quote: quote.clone() });
+            state.live.moderation(ModerationAction::Restored, Some(&id), None);
+            Ok(Json(quote))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
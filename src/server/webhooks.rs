@@ -0,0 +1,631 @@
+//! Outgoing webhooks.
+//!
+//! Admins register URLs for some of the [`EVENT_TYPES`]. Every quote event
+//! published through [`EventHub`](crate::events::EventHub) is queued in
+//! `webhook_deliveries` for each matching webhook, and a background worker
+//! POSTs it, retrying failures with exponential backoff. The table doubles as
+//! the delivery log.
+//!
+//! Each request carries `X-Webhook-Timestamp` and `X-Webhook-Signature:
+//! sha256=<hex>`, the HMAC-SHA256 of `"{timestamp}.{body}"` keyed with the
+//! webhook's secret. `examples/webhook_receiver.rs` is a receiver that checks
+//! both, for trying this out locally.
+//!
+//! There is no review queue for submissions, so `quote.approved` fires when an
+//! admin marks a quote's source as verified.
+
+use axum::{
+    extract::{ Path, Query, State },
+    http::{ header::CONTENT_TYPE, HeaderMap, StatusCode },
+    response::Json,
+};
+use chrono::{ DateTime, Duration, Utc };
+use hmac::{ Hmac, Mac };
+use rand::RngCore;
+use serde::{ Deserialize, Serialize };
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::{ mysql::{ MySqlPool, MySqlRow }, Row };
+use std::{ sync::Arc, time::Instant };
+use tokio::sync::{ broadcast::error::RecvError, Notify };
+use uuid::Uuid;
+
+use crate::{ events::QuoteEvent, require_admin, AppState };
+
+pub const EVENT_TYPES: &[&str] = &["quote.created", "quote.updated", "quote.deleted", "quote.approved"];
+
+const MAX_ATTEMPTS: u32 = 8;
+/// The first retry waits this long, and every further one twice as long.
+const BASE_BACKOFF_SECS: i64 = 10;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const BATCH_SIZE: i64 = 20;
+const DELIVERY_LOG_DAYS: i64 = 30;
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Longest response body kept in the delivery log for a failed attempt.
+const MAX_ERROR_LENGTH: usize = 500;
+
+#[derive(Debug, Serialize)]
+pub struct Webhook {
+    id: String,
+    url: String,
+    events: Vec<String>,
+    active: bool,
+    created_at: DateTime<Utc>,
+}
+
+/// Only returned when the secret is set, so it can be copied once.
+#[derive(Debug, Serialize)]
+pub struct WebhookWithSecret {
+    #[serde(flatten)]
+    webhook: Webhook,
+    secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhook {
+    url: String,
+    events: Vec<String>,
+    /// Generated when omitted.
+    secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhook {
+    url: Option<String>,
+    events: Option<Vec<String>>,
+    active: Option<bool>,
+    secret: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Delivery {
+    id: u64,
+    webhook_id: String,
+    event: String,
+    /// `pending`, `delivered` or `failed`.
+    status: String,
+    attempts: u32,
+    response_status: Option<u16>,
+    error: Option<String>,
+    payload: serde_json::Value,
+    next_attempt_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveryQuery {
+    limit: Option<u32>,
+    status: Option<String>,
+}
+
+pub async fn create_tables(pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhooks (
+            id VARCHAR(36) PRIMARY KEY,
+            url VARCHAR(2048) NOT NULL,
+            events VARCHAR(255) NOT NULL,
+            secret VARCHAR(255) NOT NULL,
+            active BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+            webhook_id VARCHAR(36) NOT NULL,
+            event VARCHAR(50) NOT NULL,
+            payload MEDIUMTEXT NOT NULL,
+            status VARCHAR(20) NOT NULL DEFAULT 'pending',
+            attempts INT UNSIGNED NOT NULL DEFAULT 0,
+            response_status SMALLINT UNSIGNED,
+            error TEXT,
+            next_attempt_at TIMESTAMP NULL DEFAULT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            delivered_at TIMESTAMP NULL DEFAULT NULL,
+            INDEX idx_webhook_deliveries_due (status, next_attempt_at),
+            INDEX idx_webhook_deliveries_webhook (webhook_id, created_at)
+        )
+        "#
+    )
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+fn webhook_from_row(row: &MySqlRow) -> Webhook {
+    let events: String = row.get("events");
+    Webhook {
+        id: row.get("id"),
+        url: row.get("url"),
+        events: events.split(',').map(str::to_string).collect(),
+        active: row.get("active"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn delivery_from_row(row: &MySqlRow) -> Delivery {
+    let payload: String = row.get("payload");
+    Delivery {
+        id: row.get("id"),
+        webhook_id: row.get("webhook_id"),
+        event: row.get("event"),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        response_status: row.get("response_status"),
+        error: row.get("error"),
+        payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+        next_attempt_at: row.get("next_attempt_at"),
+        created_at: row.get("created_at"),
+        delivered_at: row.get("delivered_at"),
+    }
+}
+
+const DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event, payload, status, attempts, response_status, error, next_attempt_at, created_at, delivered_at";
+
+fn validate_url(url: &str) -> Result<String, StatusCode> {
+    let url = url.trim();
+    if (url.starts_with("http://") || url.starts_with("https://")) && url.len() <= 2048 {
+        Ok(url.to_string())
+    } else {
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+fn validate_events(events: &[String]) -> Result<String, StatusCode> {
+    let mut events: Vec<&str> = events.iter().map(|event| event.trim()).collect();
+    events.sort_unstable();
+    events.dedup();
+    if events.is_empty() || events.iter().any(|event| !EVENT_TYPES.contains(event)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(events.join(","))
+}
+
+fn validate_secret(secret: Option<String>) -> Result<String, StatusCode> {
+    match secret.map(|secret| secret.trim().to_string()) {
+        Some(secret) if secret.len() < 16 || secret.len() > 255 => Err(StatusCode::BAD_REQUEST),
+        Some(secret) => Ok(secret),
+        None => {
+            let mut bytes = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            Ok(hex::encode(bytes))
+        }
+    }
+}
+
+/// `sha256=<hex>` over `"{timestamp}.{body}"`.
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn backoff(attempts: u32) -> Duration {
+    let seconds = BASE_BACKOFF_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(20));
+    Duration::seconds(seconds.min(MAX_BACKOFF_SECS))
+}
+
+fn event_payload(event: &QuoteEvent) -> (&'static str, serde_json::Value) {
+    let (name, data) = match event {
+        QuoteEvent::Created { quote } => ("quote.created", json!({ "quote": quote })),
+        QuoteEvent::Updated { quote } => ("quote.updated", json!({ "quote": quote })),
+        QuoteEvent::Approved { quote } => ("quote.approved", json!({ "quote": quote })),
+        QuoteEvent::Deleted { id } => ("quote.deleted", json!({ "id": id })),
+    };
+    (name, json!({ "event": name, "occurred_at": Utc::now(), "data": data }))
+}
+
+async fn enqueue(pool: &MySqlPool, event: &QuoteEvent) -> Result<(), sqlx::Error> {
+    let (name, payload) = event_payload(event);
+
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (webhook_id, event, payload, next_attempt_at)
+         SELECT id, ?, ?, ? FROM webhooks WHERE active AND FIND_IN_SET(?, events)"
+    )
+        .bind(name)
+        .bind(payload.to_string())
+        .bind(Utc::now())
+        .bind(name)
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+struct DueDelivery {
+    id: u64,
+    event: String,
+    payload: String,
+    attempts: u32,
+    url: String,
+    secret: String,
+}
+
+/// Makes one attempt and records its outcome. Without `retry` a failure is
+/// final.
+async fn attempt(
+    client: &reqwest::Client,
+    pool: &MySqlPool,
+    delivery: &DueDelivery,
+    retry: bool
+) -> Result<bool, sqlx::Error> {
+    let timestamp = Utc::now().timestamp().to_string();
+    let result = client
+        .post(&delivery.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header("X-Webhook-Event", &delivery.event)
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header("X-Webhook-Timestamp", &timestamp)
+        .header("X-Webhook-Signature", sign(&delivery.secret, &timestamp, &delivery.payload))
+        .body(delivery.payload.clone())
+        .send().await;
+
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            let body: String = body.chars().take(MAX_ERROR_LENGTH).collect();
+            (Some(status), Some(format!("HTTP {}: {}", status, body)))
+        }
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let attempts = delivery.attempts + 1;
+    let delivered = error.is_none();
+    let now = Utc::now();
+    let (status, next_attempt_at, delivered_at) = if delivered {
+        ("delivered", None, Some(now))
+    } else if !retry || attempts >= MAX_ATTEMPTS {
+        ("failed", None, None)
+    } else {
+        ("pending", Some(now + backoff(attempts)), None)
+    };
+
+    sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = ?, attempts = ?, response_status = ?, error = ?, next_attempt_at = ?, delivered_at = ?
+         WHERE id = ?"
+    )
+        .bind(status)
+        .bind(attempts)
+        .bind(response_status)
+        .bind(&error)
+        .bind(next_attempt_at)
+        .bind(delivered_at)
+        .bind(delivery.id)
+        .execute(pool).await?;
+
+    Ok(delivered)
+}
+
+const DUE_COLUMNS: &str =
+    "webhook_deliveries.id, webhook_deliveries.event, webhook_deliveries.payload,
+     webhook_deliveries.attempts, webhooks.url, webhooks.secret";
+
+fn due_from_row(row: &MySqlRow) -> DueDelivery {
+    DueDelivery {
+        id: row.get("id"),
+        event: row.get("event"),
+        payload: row.get("payload"),
+        attempts: row.get("attempts"),
+        url: row.get("url"),
+        secret: row.get("secret"),
+    }
+}
+
+async fn deliver_due(client: &reqwest::Client, pool: &MySqlPool) -> Result<(), sqlx::Error> {
+    let due: Vec<DueDelivery> = sqlx
+        ::query(
+            &format!(
+                "SELECT {} FROM webhook_deliveries
+                 JOIN webhooks ON webhooks.id = webhook_deliveries.webhook_id
+                 WHERE webhook_deliveries.status = 'pending' AND webhook_deliveries.next_attempt_at <= ?
+                   AND webhooks.active
+                 ORDER BY webhook_deliveries.next_attempt_at
+                 LIMIT ?",
+                DUE_COLUMNS
+            )
+        )
+        .bind(Utc::now())
+        .bind(BATCH_SIZE)
+        .fetch_all(pool).await?
+        .iter()
+        .map(due_from_row)
+        .collect();
+
+    let attempts = due.iter().map(|delivery| attempt(client, pool, delivery, true));
+    for result in futures_util::future::join_all(attempts).await {
+        result?;
+    }
+
+    Ok(())
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client
+        ::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent("rusted-quotes-webhooks")
+        .build()
+        .expect("Failed to build webhook HTTP client")
+}
+
+/// Starts the task that queues deliveries for quote events and the worker
+/// that sends them.
+pub fn spawn_dispatcher(state: AppState) {
+    let wake = Arc::new(Notify::new());
+
+    let mut events = state.events.subscribe();
+    let pool = state.pool.clone();
+    let queued = wake.clone();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(stored) => {
+                    if let Err(e) = enqueue(&pool, &stored.event).await {
+                        eprintln!("Database error while queueing webhooks: {}", e);
+                    }
+                    queued.notify_one();
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    eprintln!("Webhook dispatcher fell behind; {} quote event(s) were not delivered", missed);
+                }
+                Err(RecvError::Closed) => {
+                    break;
+                }
+            }
+        }
+    });
+
+    let pool = state.pool.clone();
+    tokio::spawn(async move {
+        let client = http_client();
+        let mut last_cleanup: Option<Instant> = None;
+
+        loop {
+            tokio::select! {
+                _ = wake.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+
+            if let Err(e) = deliver_due(&client, &pool).await {
+                eprintln!("Database error while delivering webhooks: {}", e);
+            }
+
+            if last_cleanup.is_none_or(|at| at.elapsed() > CLEANUP_INTERVAL) {
+                last_cleanup = Some(Instant::now());
+                let cutoff = Utc::now() - Duration::days(DELIVERY_LOG_DAYS);
+                if let Err(e) = sqlx::query("DELETE FROM webhook_deliveries WHERE created_at < ?")
+                    .bind(cutoff)
+                    .execute(&pool).await
+                {
+                    eprintln!("Database error while pruning webhook deliveries: {}", e);
+                }
+            }
+        }
+    });
+}
+
+fn database_error(e: sqlx::Error) -> StatusCode {
+    eprintln!("Database error: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+async fn fetch_webhook(pool: &MySqlPool, id: &str) -> Result<Webhook, StatusCode> {
+    sqlx::query("SELECT id, url, events, active, created_at FROM webhooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool).await
+        .map_err(database_error)?
+        .map(|row| webhook_from_row(&row))
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap
+) -> Result<Json<Vec<Webhook>>, StatusCode> {
+    require_admin(&headers)?;
+
+    let rows = sqlx
+        ::query("SELECT id, url, events, active, created_at FROM webhooks ORDER BY created_at")
+        .fetch_all(&state.pool).await
+        .map_err(database_error)?;
+
+    Ok(Json(rows.iter().map(webhook_from_row).collect()))
+}
+
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateWebhook>
+) -> Result<Json<WebhookWithSecret>, StatusCode> {
+    require_admin(&headers)?;
+
+    let url = validate_url(&payload.url)?;
+    let events = validate_events(&payload.events)?;
+    let secret = validate_secret(payload.secret)?;
+    let id = Uuid::new_v4().to_string();
+
+    sqlx::query("INSERT INTO webhooks (id, url, events, secret, active, created_at) VALUES (?, ?, ?, ?, TRUE, ?)")
+        .bind(&id)
+        .bind(&url)
+        .bind(&events)
+        .bind(&secret)
+        .bind(Utc::now())
+        .execute(&state.pool).await
+        .map_err(database_error)?;
+
+    let webhook = fetch_webhook(&state.pool, &id).await?;
+    Ok(Json(WebhookWithSecret { webhook, secret }))
+}
+
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateWebhook>
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    require_admin(&headers)?;
+
+    let current = fetch_webhook(&state.pool, &id).await?;
+    let url = match &payload.url {
+        Some(url) => validate_url(url)?,
+        None => current.url,
+    };
+    let events = validate_events(payload.events.as_ref().unwrap_or(&current.events))?;
+    let active = payload.active.unwrap_or(current.active);
+    let secret = match payload.secret {
+        Some(secret) => Some(validate_secret(Some(secret))?),
+        None => None,
+    };
+
+    sqlx::query("UPDATE webhooks SET url = ?, events = ?, active = ?, secret = COALESCE(?, secret) WHERE id = ?")
+        .bind(&url)
+        .bind(&events)
+        .bind(active)
+        .bind(&secret)
+        .bind(&id)
+        .execute(&state.pool).await
+        .map_err(database_error)?;
+
+    let webhook = fetch_webhook(&state.pool, &id).await?;
+    let body = match secret {
+        Some(secret) => json!(WebhookWithSecret { webhook, secret }),
+        None => json!(webhook),
+    };
+    Ok(Json(body))
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&headers)?;
+
+    let result = async {
+        let mut tx = state.pool.begin().await?;
+        let deleted = sqlx::query("DELETE FROM webhooks WHERE id = ?").bind(&id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ?").bind(&id).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(deleted.rows_affected())
+    }.await;
+
+    match result {
+        Ok(0) => Err(StatusCode::NOT_FOUND),
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(database_error(e)),
+    }
+}
+
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(params): Query<DeliveryQuery>
+) -> Result<Json<Vec<Delivery>>, StatusCode> {
+    require_admin(&headers)?;
+    fetch_webhook(&state.pool, &id).await?;
+
+    let limit = params.limit.unwrap_or(50).min(200);
+    let sql = format!(
+        "SELECT {} FROM webhook_deliveries WHERE webhook_id = ? {} ORDER BY id DESC LIMIT ?",
+        DELIVERY_COLUMNS,
+        if params.status.is_some() { "AND status = ?" } else { "" }
+    );
+
+    let mut query = sqlx::query(&sql).bind(&id);
+    if let Some(status) = &params.status {
+        query = query.bind(status);
+    }
+    let rows = query.bind(limit).fetch_all(&state.pool).await.map_err(database_error)?;
+
+    Ok(Json(rows.iter().map(delivery_from_row).collect()))
+}
+
+async fn fetch_delivery(pool: &MySqlPool, webhook_id: &str, delivery_id: u64) -> Result<Delivery, StatusCode> {
+    sqlx::query(&format!("SELECT {} FROM webhook_deliveries WHERE id = ? AND webhook_id = ?", DELIVERY_COLUMNS))
+        .bind(delivery_id)
+        .bind(webhook_id)
+        .fetch_optional(pool).await
+        .map_err(database_error)?
+        .map(|row| delivery_from_row(&row))
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Sends a `ping` right away and returns the logged delivery, so a receiver
+/// can be checked without waiting for real quote activity.
+pub async fn test_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>
+) -> Result<Json<Delivery>, StatusCode> {
+    require_admin(&headers)?;
+
+    let webhook = sqlx
+        ::query("SELECT url, secret FROM webhooks WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool).await
+        .map_err(database_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let payload = json!({ "event": "ping", "occurred_at": Utc::now(), "data": {} }).to_string();
+    let inserted = sqlx
+        ::query(
+            "INSERT INTO webhook_deliveries (webhook_id, event, payload, status, next_attempt_at)
+             VALUES (?, 'ping', ?, 'pending', NULL)"
+        )
+        .bind(&id)
+        .bind(&payload)
+        .execute(&state.pool).await
+        .map_err(database_error)?;
+    let delivery_id = inserted.last_insert_id();
+
+    let due = DueDelivery {
+        id: delivery_id,
+        event: "ping".to_string(),
+        payload,
+        attempts: 0,
+        url: webhook.get("url"),
+        secret: webhook.get("secret"),
+    };
+    attempt(&http_client(), &state.pool, &due, false).await.map_err(database_error)?;
+
+    Ok(Json(fetch_delivery(&state.pool, &id, delivery_id).await?))
+}
+
+/// Queues a delivery again, whatever its state, with a fresh set of attempts.
+pub async fn redeliver(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((id, delivery_id)): Path<(String, u64)>
+) -> Result<Json<Delivery>, StatusCode> {
+    require_admin(&headers)?;
+
+    let updated = sqlx
+        ::query(
+            "UPDATE webhook_deliveries SET status = 'pending', attempts = 0, next_attempt_at = ?
+             WHERE id = ? AND webhook_id = ?"
+        )
+        .bind(Utc::now())
+        .bind(delivery_id)
+        .bind(&id)
+        .execute(&state.pool).await
+        .map_err(database_error)?;
+    if updated.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(fetch_delivery(&state.pool, &id, delivery_id).await?))
+}
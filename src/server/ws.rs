@@ -0,0 +1,187 @@
+//! Two-way live channel at `/api/ws`.
+//!
+//! Every connection receives like counts and "currently viewing" counts as
+//! they change. A connection that authenticates with the admin token, either
+//! through the upgrade request's `Authorization` header or an `authenticate`
+//! message, also receives moderation notices. Message types live in
+//! `src/protocol.rs`, which the WASM client shares.
+
+use axum::{
+    extract::{ ws::{ Message, WebSocket, WebSocketUpgrade }, State },
+    http::HeaderMap,
+    response::Response,
+};
+use std::{ collections::HashMap, sync::Mutex };
+use tokio::sync::broadcast::{ self, error::RecvError };
+
+use crate::{
+    is_admin_token,
+    protocol::{ ClientMessage, ModerationAction, ServerMessage },
+    require_admin,
+    AppState,
+};
+
+const CHANNEL_CAPACITY: usize = 256;
+/// Longest rejected submission text forwarded to moderators.
+const MAX_DETAIL_CHARS: usize = 200;
+
+pub struct LiveHub {
+    updates: broadcast::Sender<ServerMessage>,
+    moderation: broadcast::Sender<ServerMessage>,
+    viewers: Mutex<HashMap<String, u32>>,
+}
+
+impl Default for LiveHub {
+    fn default() -> Self {
+        LiveHub {
+            updates: broadcast::channel(CHANNEL_CAPACITY).0,
+            moderation: broadcast::channel(CHANNEL_CAPACITY).0,
+            viewers: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl LiveHub {
+    pub fn like_count(&self, quote_id: &str, like_count: u32) {
+        let _ = self.updates.send(ServerMessage::LikeCount {
+            quote_id: quote_id.to_string(),
+            like_count,
+        });
+    }
+
+    pub fn moderation(&self, action: ModerationAction, quote_id: Option<&str>, detail: Option<&str>) {
+        let _ = self.moderation.send(ServerMessage::Moderation {
+            action,
+            quote_id: quote_id.map(str::to_string),
+            detail: detail.map(|detail| detail.chars().take(MAX_DETAIL_CHARS).collect()),
+        });
+    }
+
+    fn viewers(&self, quote_id: &str) -> u32 {
+        self.viewers.lock().unwrap().get(quote_id).copied().unwrap_or(0)
+    }
+
+    fn join(&self, quote_id: &str) {
+        let viewers = {
+            let mut viewers = self.viewers.lock().unwrap();
+            let count = viewers.entry(quote_id.to_string()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        self.announce(quote_id, viewers);
+    }
+
+    fn leave(&self, quote_id: &str) {
+        let viewers = {
+            let mut viewers = self.viewers.lock().unwrap();
+            let count = viewers.get_mut(quote_id).map(|count| {
+                *count = count.saturating_sub(1);
+                *count
+            });
+            if count == Some(0) {
+                viewers.remove(quote_id);
+            }
+            count.unwrap_or(0)
+        };
+        self.announce(quote_id, viewers);
+    }
+
+    fn announce(&self, quote_id: &str, viewers: u32) {
+        let _ = self.updates.send(ServerMessage::Presence {
+            quote_id: quote_id.to_string(),
+            viewers,
+        });
+    }
+}
+
+pub async fn connect(ws: WebSocketUpgrade, State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let admin = require_admin(&headers).is_ok();
+    ws.on_upgrade(move |socket| handle_socket(socket, state, admin))
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(message).expect("server messages always serialize");
+    socket.send(Message::Text(json)).await
+}
+
+/// Per-connection state that outlives a single message.
+struct Connection {
+    admin: bool,
+    viewing: Option<String>,
+}
+
+fn handle_message(state: &AppState, connection: &mut Connection, message: ClientMessage) -> Option<ServerMessage> {
+    match message {
+        ClientMessage::View { quote_id } => {
+            if connection.viewing.as_ref() == Some(&quote_id) {
+                return Some(ServerMessage::Presence { viewers: state.live.viewers(&quote_id), quote_id });
+            }
+            if let Some(previous) = connection.viewing.take() {
+                state.live.leave(&previous);
+            }
+            state.live.join(&quote_id);
+            connection.viewing = Some(quote_id);
+            // Everyone, this connection included, gets the new count through
+            // the broadcast.
+            None
+        }
+        ClientMessage::Leave => {
+            if let Some(previous) = connection.viewing.take() {
+                state.live.leave(&previous);
+            }
+            None
+        }
+        ClientMessage::Authenticate { token } => {
+            connection.admin = connection.admin || is_admin_token(&token);
+            Some(ServerMessage::Authenticated { admin: connection.admin })
+        }
+        ClientMessage::Ping => Some(ServerMessage::Pong),
+    }
+}
+
+async fn handle_socket(mut socket: WebSocket, state: AppState, admin: bool) {
+    let mut updates = state.live.updates.subscribe();
+    let mut moderation = state.live.moderation.subscribe();
+    let mut connection = Connection { admin, viewing: None };
+
+    if admin && send(&mut socket, &ServerMessage::Authenticated { admin: true }).await.is_err() {
+        return;
+    }
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(message) => handle_message(&state, &mut connection, message),
+                    Err(e) => Some(ServerMessage::Error { message: format!("Invalid message: {}", e) }),
+                },
+                // Pings are answered by axum; binary frames are not part of
+                // the protocol.
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Binary(_))) => None,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            update = updates.recv() => match update {
+                Ok(message) => Some(message),
+                // A slow client just skips some counts; the next ones
+                // correct it.
+                Err(RecvError::Lagged(_)) => None,
+                Err(RecvError::Closed) => break,
+            },
+            notice = moderation.recv(), if connection.admin => match notice {
+                Ok(message) => Some(message),
+                Err(RecvError::Lagged(_)) => None,
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if let Some(message) = outgoing {
+            if send(&mut socket, &message).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    if let Some(quote_id) = connection.viewing {
+        state.live.leave(&quote_id);
+    }
+}
@@ -0,0 +1,506 @@
+//! Markup for the parts of the page built from data.
+//!
+//! Each view is a plain function from data to `Markup`, checked at compile
+//! time by `maud`, which also escapes every interpolated value. They never
+//! touch the DOM, so they can be rendered outside a browser; the caller sets
+//! the result with `set_inner_html` and wires up the element ids and classes
+//! the handlers in `lib.rs` look for.
+
+use maud::{ html, Markup };
+
+use crate::{ client::QuoteListQuery, truncate_preview, Quote, Route };
+
+/// The date part of an RFC 3339 timestamp.
+fn date(timestamp: &str) -> &str {
+    timestamp.split('T').next().unwrap_or("")
+}
+
+fn close_icon() -> Markup {
+    html! {
+        svg class="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24" {
+            path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M6 18L18 6M6 6l12 12" {}
+        }
+    }
+}
+
+/// A card in the quote grid. Text longer than `preview_length` graphemes
+/// starts collapsed behind a "Show more" button.
+pub fn quote_card(quote: &Quote, liked: bool, preview_length: usize) -> Markup {
+    let preview = truncate_preview(&quote.quote, preview_length);
+
+    html! {
+        div class="glass-morphism rounded-2xl p-6 cyber-border card-hover cursor-pointer quote-card group" data-id=(quote.id) {
+            div class="space-y-4" {
+                div class="flex items-start justify-between" {
+                    div class="flex-1" {
+                        blockquote class="text-gray-100 font-medium leading-relaxed text-lg group-hover:text-white transition-colors" {
+                            "\""
+                            @if let Some(preview) = preview {
+                                span class="quote-preview" { (preview) "…" }
+                                span class="quote-full hidden" { (quote.quote) }
+                            } @else {
+                                (quote.quote)
+                            }
+                            "\""
+                        }
+                        @if preview.is_some() {
+                            button class="expand-btn mt-2 text-xs text-neon-purple font-mono hover:text-neon-cyan transition-colors" aria-expanded="false" {
+                                "Show more"
+                            }
+                        }
+                    }
+                    div class="ml-4 opacity-50 group-hover:opacity-100 transition-opacity" {
+                        svg class="w-5 h-5 text-neon-cyan" fill="currentColor" viewBox="0 0 20 20" {
+                            path fill-rule="evenodd" d="M18 10a8 8 0 11-16 0 8 8 0 0116 0zm-6-3a2 2 0 11-4 0 2 2 0 014 0zm-2 4a5 5 0 00-4.546 2.916A5.986 5.986 0 0010 16a5.986 5.986 0 004.546-2.084A5 5 0 0010 11z" clip-rule="evenodd" {}
+                        }
+                    }
+                }
+
+                div class="flex items-center justify-between pt-4 border-t border-dark-700 group-hover:border-neon-cyan/30 transition-colors" {
+                    cite class="text-neon-cyan font-bold group-hover:text-white transition-colors" {
+                        "— " (quote.author.as_deref().unwrap_or("Anonymous"))
+                    }
+                    div class="flex items-center gap-4" {
+                        (like_button(&quote.id, quote.like_count, liked))
+                        div class="text-xs text-gray-500 font-mono group-hover:text-gray-400 transition-colors" {
+                            (date(&quote.created_at))
+                        }
+                    }
+                }
+
+                div class="opacity-0 group-hover:opacity-100 transition-all duration-300 text-center" {
+                    div class="text-xs text-neon-purple font-mono" { "Click to expand" }
+                }
+            }
+        }
+    }
+}
+
+pub fn like_button(quote_id: &str, like_count: u32, liked: bool) -> Markup {
+    let color = if liked { "text-neon-pink" } else { "text-gray-500 hover:text-neon-pink" };
+
+    html! {
+        button class={ "like-btn flex items-center gap-1 text-sm font-mono transition-colors " (color) } data-id=(quote_id) title="Like" {
+            svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20" {
+                path fill-rule="evenodd" d="M3.172 5.172a4 4 0 015.656 0L10 6.343l1.172-1.171a4 4 0 115.656 5.656L10 17.657l-6.828-6.829a4 4 0 010-5.656z" clip-rule="evenodd" {}
+            }
+            span class="like-count" { (like_count) }
+        }
+    }
+}
+
+/// Fills the grid when a list comes back empty.
+pub fn empty_quotes(filtered: bool) -> Markup {
+    html! {
+        div class="col-span-full" {
+            div class="glass-morphism rounded-2xl p-12 text-center cyber-border" {
+                div class="space-y-4" {
+                    div class="text-6xl" { "📝" }
+                    @if filtered {
+                        div class="text-white text-xl font-bold" { "No matching quotes" }
+                        p class="text-gray-400" { "Nothing here matches this filter yet." }
+                        a href=(Route::home().fragment()) class="inline-block text-neon-cyan font-mono text-sm hover:underline" {
+                            "Show all quotes"
+                        }
+                    } @else {
+                        div class="text-white text-xl font-bold" { "No quotes found" }
+                        p class="text-gray-400" { "Be the first to share your wisdom with the world!" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The author and tag filters on `list`, each with a link to the list
+/// without it.
+pub fn filter_chips(list: &QuoteListQuery) -> Markup {
+    let without_author = QuoteListQuery { author: None, ..list.clone() };
+    let without_tag = QuoteListQuery { tag: None, ..list.clone() };
+
+    html! {
+        @if let Some(name) = &list.author {
+            (filter_chip("by ", name, &Route::Quotes(without_author).fragment()))
+        }
+        @if let Some(tag) = &list.tag {
+            (filter_chip("#", tag, &Route::Quotes(without_tag).fragment()))
+        }
+    }
+}
+
+fn filter_chip(prefix: &str, value: &str, remove_href: &str) -> Markup {
+    html! {
+        span class="filter-chip inline-flex items-center gap-2 px-3 py-1 rounded-full border border-neon-purple/40 text-neon-purple font-mono text-sm" {
+            span { (prefix) (value) }
+            a href=(remove_href) class="text-gray-400 hover:text-neon-cyan transition-colors" title="Remove filter" { "✕" }
+        }
+    }
+}
+
+/// What `#list-end`, under the quote grid, shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    /// Before the first page, and under an empty list.
+    Hidden,
+    /// There is another page.
+    More,
+    Loading,
+    /// The last page is shown.
+    End,
+    /// The last attempt to load another page failed.
+    Failed,
+}
+
+pub fn list_end(state: ListEnd) -> Markup {
+    let button = |label: &str| {
+        html! {
+            button class="load-more-btn bg-dark-800 border border-neon-cyan/40 text-neon-cyan font-mono text-sm py-2 px-6 rounded-xl hover:bg-dark-700 hover:border-neon-cyan transition-all" {
+                (label)
+            }
+        }
+    };
+
+    html! {
+        @match state {
+            ListEnd::Hidden => {}
+            ListEnd::More => (button("Load more")),
+            ListEnd::Loading => {
+                div class="animate-pulse text-gray-400 font-mono text-sm" { "Loading more quotes..." }
+            }
+            ListEnd::End => {
+                div class="text-gray-500 font-mono text-xs tracking-wider" { "END_OF_LIST" }
+            }
+            ListEnd::Failed => {
+                div class="space-y-3" {
+                    p class="text-red-400 font-mono text-sm" { "Could not load more quotes." }
+                    (button("Try again"))
+                }
+            }
+        }
+    }
+}
+
+pub fn error_modal(message: &str) -> Markup {
+    html! {
+        div class="glass-morphism rounded-3xl p-8 cyber-border max-w-md w-full mx-auto transform transition-all" {
+            div class="flex justify-between items-center mb-6" {
+                div {
+                    h3 class="text-2xl font-bold text-red-400 mb-2" { "System Error" }
+                    p class="text-gray-400 font-mono text-sm" { "Something went wrong" }
+                }
+                button id="modal-close" class="text-gray-400 hover:text-red-400 transition-colors p-2 rounded-lg hover:bg-dark-800" {
+                    (close_icon())
+                }
+            }
+
+            div class="text-center space-y-6" {
+                div class="text-6xl" { "⚠️" }
+                div class="space-y-3" {
+                    p class="text-gray-300 text-lg" { (message) }
+                    div class="bg-dark-900 border border-red-500/20 rounded-lg p-4" {
+                        p class="text-red-400 font-mono text-sm" { "ERROR_CODE: CONNECTION_FAILED" }
+                    }
+                }
+                button id="close-error" class="bg-red-500 hover:bg-red-600 text-white font-bold py-3 px-8 rounded-xl transition-all transform hover:scale-105 shadow-lg" {
+                    span class="flex items-center gap-2" {
+                        svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20" {
+                            path fill-rule="evenodd" d="M16.707 5.293a1 1 0 010 1.414l-8 8a1 1 0 01-1.414 0l-4-4a1 1 0 011.414-1.414L8 12.586l7.293-7.293a1 1 0 011.414 0z" clip-rule="evenodd" {}
+                        }
+                        "Acknowledge"
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn add_quote_form() -> Markup {
+    html! {
+        div class="glass-morphism rounded-3xl p-8 cyber-border max-w-md w-full mx-auto transform transition-all" {
+            div class="flex justify-between items-center mb-8" {
+                div {
+                    h3 class="text-3xl font-bold text-white mb-2" { "Add New Quote" }
+                    p class="text-gray-400 font-mono text-sm" { "Share a line worth remembering" }
+                }
+                button id="modal-close" class="text-gray-400 hover:text-neon-cyan transition-colors p-2 rounded-lg hover:bg-dark-800" {
+                    (close_icon())
+                }
+            }
+
+            form id="quote-form" class="space-y-6" {
+                div {
+                    label for="quote-input" class="flex items-center gap-2 text-sm font-bold text-neon-cyan mb-3 font-mono" {
+                        svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20" {
+                            path fill-rule="evenodd" d="M18 13V5a2 2 0 00-2-2H4a2 2 0 00-2 2v8a2 2 0 002 2h3l3 3 3-3h3a2 2 0 002-2zM5 7a1 1 0 011-1h8a1 1 0 110 2H6a1 1 0 01-1-1zm1 3a1 1 0 100 2h3a1 1 0 100-2H6z" clip-rule="evenodd" {}
+                        }
+                        "QUOTE_TEXT"
+                    }
+                    textarea
+                        id="quote-input"
+                        rows="4"
+                        class="w-full px-4 py-4 bg-dark-900 border border-dark-700 rounded-xl focus:ring-2 focus:ring-neon-cyan focus:border-transparent resize-none text-gray-100 placeholder-gray-500 transition-all font-primary"
+                        placeholder="Enter your inspiring quote..."
+                        required {}
+                }
+
+                div {
+                    label for="author-input" class="flex items-center gap-2 text-sm font-bold text-neon-purple mb-3 font-mono" {
+                        svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20" {
+                            path fill-rule="evenodd" d="M10 9a3 3 0 100-6 3 3 0 000 6zm-7 9a7 7 0 1114 0H3z" clip-rule="evenodd" {}
+                        }
+                        "AUTHOR (Optional)"
+                    }
+                    input
+                        type="text"
+                        id="author-input"
+                        class="w-full px-4 py-4 bg-dark-900 border border-dark-700 rounded-xl focus:ring-2 focus:ring-neon-purple focus:border-transparent text-gray-100 placeholder-gray-500 transition-all font-primary"
+                        placeholder="Author name";
+                }
+
+                div class="flex gap-4 pt-4" {
+                    button type="submit" class="flex-1 bg-gradient-to-r from-neon-purple to-neon-cyan text-dark-950 font-bold py-4 px-6 rounded-xl hover:from-neon-cyan hover:to-neon-purple transition-all transform hover:scale-105 shadow-lg hover:shadow-neon-cyan/25" {
+                        span class="flex items-center justify-center gap-2" {
+                            svg class="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24" {
+                                path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 6v6m0 0v6m0-6h6m-6 0H6" {}
+                            }
+                            "Deploy Quote"
+                        }
+                    }
+                    button type="button" id="cancel-btn" class="flex-1 bg-dark-800 border border-dark-600 text-gray-300 font-bold py-4 px-6 rounded-xl hover:bg-dark-700 hover:border-dark-500 transition-all" {
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The detail modal. Translations, the source, viewers and the collection
+/// picker start empty and hidden; `show_quote_modal` fills them in. Author
+/// and tag links keep the order and page size of `list`, the list open
+/// underneath.
+pub fn quote_modal(quote: &Quote, liked: bool, share_url: &str, list: &QuoteListQuery) -> Markup {
+    let cite = |name: &str| {
+        html! {
+            cite id="modal-quote-author" class="text-xl text-neon-cyan font-bold" { "— " (name) }
+        }
+    };
+
+    html! {
+        div class="glass-morphism rounded-3xl p-8 cyber-border max-w-4xl w-full mx-auto transform transition-all" {
+            div class="mb-8" {
+                div {
+                    h3 class="text-3xl font-bold text-white mb-2" { "Quote Details" }
+                    p class="text-gray-400 font-mono text-sm" {
+                        "ID: " (quote.id)
+                        @if let Some(language) = &quote.language {
+                            " · " (language.to_uppercase())
+                        }
+                    }
+                }
+            }
+
+            div class="text-center space-y-8" {
+                div class="relative" {
+                    div class="absolute -top-4 -left-4 text-6xl text-neon-cyan opacity-20" { "\"" }
+                    blockquote id="modal-quote-text" class="text-2xl md:text-3xl font-medium text-white leading-relaxed px-8" {
+                        (quote.quote)
+                    }
+                    div class="absolute -bottom-4 -right-4 text-6xl text-neon-cyan opacity-20" { "\"" }
+                }
+
+                div id="translation-toggle" class="hidden flex flex-wrap justify-center gap-2" {}
+
+                div class="space-y-4" {
+                    @if let Some(name) = &quote.author {
+                        a href=(Route::Quotes(list.for_author(name)).fragment()) class="hover:underline" title="More by this author" {
+                            (cite(name))
+                        }
+                    } @else {
+                        (cite("Anonymous"))
+                    }
+                    div class="flex flex-wrap justify-center gap-2 font-mono text-sm" {
+                        @for tag in &quote.tags {
+                            a href=(Route::Quotes(list.for_tag(tag)).fragment()) class="text-neon-purple hover:text-neon-cyan transition-colors" {
+                                "#" (tag)
+                            }
+                        }
+                    }
+                    div class="flex justify-center text-lg" {
+                        (like_button(&quote.id, quote.like_count, liked))
+                    }
+                    div id="quote-viewers" data-id=(quote.id) class="hidden text-neon-purple font-mono text-xs" {}
+                    div class="inline-flex items-center gap-2 text-gray-400 font-mono text-sm" {
+                        svg class="w-4 h-4" fill="currentColor" viewBox="0 0 20 20" {
+                            path fill-rule="evenodd" d="M6 2a1 1 0 00-1 1v1H4a2 2 0 00-2 2v10a2 2 0 002 2h12a2 2 0 002-2V6a2 2 0 00-2-2h-1V3a1 1 0 10-2 0v1H7V3a1 1 0 00-1-1zm0 5a1 1 0 000 2h8a1 1 0 100-2H6z" clip-rule="evenodd" {}
+                        }
+                        "Added on " (date(&quote.created_at))
+                    }
+                }
+
+                div id="quote-source" class="hidden bg-dark-900 border border-dark-700 rounded-xl p-6 text-left space-y-3" {
+                    div class="flex items-center justify-between gap-4" {
+                        div class="text-neon-cyan font-mono text-xs tracking-wider" { "SOURCE" }
+                        span id="source-verified" class="hidden text-xs font-mono text-green-400 border border-green-400/40 rounded-full px-3 py-1" {
+                            "verified"
+                        }
+                    }
+                    div id="source-details" class="text-gray-300 text-sm space-y-1" {}
+                    div class="flex items-center gap-3 pt-2" {
+                        label for="citation-style" class="text-neon-purple font-mono text-xs tracking-wider" { "CITE_AS" }
+                        select id="citation-style" class="px-3 py-1 bg-dark-950 border border-dark-700 rounded-lg text-gray-100 text-sm font-mono" {
+                            option value="apa" { "APA" }
+                            option value="mla" { "MLA" }
+                            option value="chicago" { "Chicago" }
+                        }
+                    }
+                    p id="citation-text" class="text-gray-200 text-sm font-mono break-words select-all" {}
+                }
+
+                div class="flex flex-col sm:flex-row gap-4 justify-center pt-4" {
+                    button id="another-random" class="bg-gradient-to-r from-neon-purple to-neon-cyan text-dark-950 font-bold py-3 px-8 rounded-xl hover:from-neon-cyan hover:to-neon-purple transition-all transform hover:scale-105 shadow-lg hover:shadow-neon-cyan/25" {
+                        span class="flex items-center gap-2" {
+                            svg class="w-5 h-5" fill="currentColor" viewBox="0 0 20 20" {
+                                path fill-rule="evenodd" d="M4 2a1 1 0 011 1v2.101a7.002 7.002 0 0111.601 2.566 1 1 0 11-1.885.666A5.002 5.002 0 005.999 7H9a1 1 0 010 2H4a1 1 0 01-1-1V3a1 1 0 011-1zm.008 9.057a1 1 0 011.276.61A5.002 5.002 0 0014.001 13H11a1 1 0 110-2h5a1 1 0 011 1v5a1 1 0 11-2 0v-2.101a7.002 7.002 0 01-11.601-2.566 1 1 0 01.61-1.276z" clip-rule="evenodd" {}
+                            }
+                            "Another Quote"
+                        }
+                    }
+
+                    button id="save-to-collection" class="bg-dark-800 border border-neon-purple/40 text-neon-purple font-bold py-3 px-8 rounded-xl hover:bg-dark-700 hover:border-neon-purple transition-all" {
+                        span class="flex items-center gap-2" {
+                            svg class="w-5 h-5" fill="currentColor" viewBox="0 0 20 20" {
+                                path d="M5 4a2 2 0 012-2h6a2 2 0 012 2v14l-5-2.5L5 18V4z" {}
+                            }
+                            "Save to Collection"
+                        }
+                    }
+
+                    a id="share-quote" href=(share_url) target="_blank" rel="noopener" class="bg-dark-800 border border-neon-pink/40 text-neon-pink font-bold py-3 px-8 rounded-xl hover:bg-dark-700 hover:border-neon-pink transition-all" {
+                        span class="flex items-center gap-2" {
+                            svg class="w-5 h-5" fill="currentColor" viewBox="0 0 20 20" {
+                                path d="M15 8a3 3 0 10-2.977-2.63l-4.94 2.47a3 3 0 100 4.319l4.94 2.47a3 3 0 10.895-1.789l-4.94-2.47a3.027 3.027 0 000-.74l4.94-2.47C13.456 7.68 14.19 8 15 8z" {}
+                            }
+                            "Share Card"
+                        }
+                    }
+
+                    button id="close-modal-btn" class="bg-dark-800 border border-dark-600 text-gray-300 font-bold py-3 px-8 rounded-xl hover:bg-dark-700 hover:border-dark-500 transition-all" {
+                        span class="flex items-center gap-2" { "Close" }
+                    }
+                }
+
+                div id="collection-picker" class="hidden bg-dark-900 border border-dark-700 rounded-xl p-6 text-left space-y-4" {
+                    div class="text-neon-purple font-mono text-xs tracking-wider" { "SAVE_TO_COLLECTION" }
+                    div id="collection-options" class="flex flex-wrap gap-2" {}
+                    form id="new-collection-form" class="flex flex-col sm:flex-row gap-3" {
+                        input
+                            type="text"
+                            id="new-collection-name"
+                            maxlength="100"
+                            class="flex-1 px-4 py-2 bg-dark-950 border border-dark-700 rounded-lg focus:ring-2 focus:ring-neon-purple focus:border-transparent text-gray-100 placeholder-gray-500"
+                            placeholder="New collection name";
+                        label class="flex items-center gap-2 text-sm text-gray-400 font-mono" {
+                            input type="checkbox" id="new-collection-public" class="accent-neon-purple";
+                            "public"
+                        }
+                        button type="submit" class="bg-neon-purple text-dark-950 font-bold py-2 px-4 rounded-lg hover:bg-neon-cyan transition-colors" {
+                            "Create & Save"
+                        }
+                    }
+                    p id="collection-status" class="text-sm text-gray-400 font-mono" {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::QuoteSort;
+
+    fn quote(text: &str, author: Option<&str>) -> Quote {
+        Quote {
+            id: "q1".to_string(),
+            quote: text.to_string(),
+            author: author.map(str::to_string),
+            tags: Vec::new(),
+            like_count: 3,
+            source: None,
+            language: None,
+            translation_of: None,
+            created_at: "2024-05-01T12:00:00Z".to_string(),
+            updated_at: "2024-05-01T12:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn cards_escape_the_quote_and_author() {
+        let html = quote_card(&quote("<script>alert(1)</script>", Some("Tom & \"Jerry\"")), false, 200).into_string();
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("Tom &amp; &quot;Jerry&quot;"));
+        assert!(html.contains("2024-05-01<"));
+    }
+
+    #[test]
+    fn attributes_are_escaped() {
+        let mut card = quote("Hi", None);
+        card.id = "\"><img src=x onerror=alert(1)>".to_string();
+        let html = quote_card(&card, true, 200).into_string();
+        assert!(!html.contains("<img"));
+        assert!(html.contains("data-id=\"&quot;&gt;&lt;img src=x onerror=alert(1)&gt;\""));
+        assert!(html.contains("— Anonymous"));
+    }
+
+    #[test]
+    fn long_quotes_start_collapsed() {
+        let html = quote_card(&quote("One two three four five six", None), false, 10).into_string();
+        assert!(html.contains("<span class=\"quote-preview\">One two…</span>"));
+        assert!(html.contains("expand-btn"));
+
+        let html = quote_card(&quote("One two", None), false, 10).into_string();
+        assert!(!html.contains("quote-preview"));
+        assert!(!html.contains("expand-btn"));
+    }
+
+    #[test]
+    fn modal_links_keep_the_list_order() {
+        let mut detail = quote("<b>bold</b>", Some("Tom & Jerry"));
+        detail.tags = vec!["c++".to_string()];
+        let list = QuoteListQuery { sort: QuoteSort::Popular, search: Some("ignored".to_string()), ..Default::default() };
+        let html = quote_modal(&detail, false, "https://example.com/?a=1&b=2", &list).into_string();
+
+        assert!(html.contains("&lt;b&gt;bold&lt;/b&gt;"));
+        assert!(html.contains("href=\"#/authors/Tom%20%26%20Jerry?sort=popular\""));
+        assert!(html.contains("href=\"#/tags/c%2B%2B?sort=popular\""));
+        assert!(!html.contains("ignored"));
+    }
+
+    #[test]
+    fn filter_chips_link_to_the_list_without_them() {
+        let list = QuoteListQuery {
+            author: Some("Ada <Lovelace>".to_string()),
+            tag: Some("math".to_string()),
+            limit: Some(20),
+            ..Default::default()
+        };
+        let html = filter_chips(&list).into_string();
+
+        assert!(html.contains("<span>by Ada &lt;Lovelace&gt;</span>"));
+        assert!(html.contains("href=\"#/tags/math?limit=20\""));
+        assert!(html.contains("<span>#math</span>"));
+        assert!(html.contains("href=\"#/authors/Ada%20%3CLovelace%3E?limit=20\""));
+        assert_eq!(filter_chips(&QuoteListQuery::default()).into_string(), "");
+    }
+
+    #[test]
+    fn only_the_end_states_with_actions_have_a_button() {
+        assert_eq!(list_end(ListEnd::Hidden).into_string(), "");
+        assert!(list_end(ListEnd::More).into_string().contains("load-more-btn"));
+        assert!(list_end(ListEnd::Failed).into_string().contains("load-more-btn"));
+        assert!(!list_end(ListEnd::Loading).into_string().contains("load-more-btn"));
+        assert!(!list_end(ListEnd::End).into_string().contains("load-more-btn"));
+    }
+}